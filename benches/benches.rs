@@ -37,6 +37,46 @@ fn bench_merge_recursive(c: &mut criterion::Criterion) {
     });
 }
 
+#[cfg(feature = "merge")]
+fn bench_merge_owned(c: &mut criterion::Criterion) {
+    use criterion::BatchSize;
+    use serde_json::{Map, Value};
+    use serde_json_merge::{Dfs, Merge, Union};
+
+    let mut group = c.benchmark_group("merge/owned");
+    configure_group(&mut group);
+    let value = &*COMPLEX_JSON_1;
+    let empty = || Value::Object(Map::default());
+    group.bench_function("merge/borrowed", |b| {
+        b.iter_batched(
+            empty,
+            |mut base| base.merge_recursive::<Dfs>(black_box(value)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("merge/owned", |b| {
+        b.iter_batched(
+            || (empty(), value.clone()),
+            |(mut base, other)| base.merge_owned(black_box(other)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("union/borrowed", |b| {
+        b.iter_batched(
+            empty,
+            |mut base| base.union_recursive::<Dfs>(black_box(value)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("union/owned", |b| {
+        b.iter_batched(
+            || (empty(), value.clone()),
+            |(mut base, other)| base.union_owned(black_box(other)),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
 #[cfg(feature = "sort")]
 fn bench_sort_recursive(c: &mut criterion::Criterion) {
     let mut group = c.benchmark_group("sort/recursive");
@@ -92,7 +132,7 @@ fn bench_sort_recursive(c: &mut criterion::Criterion) {
 criterion_group!(bench_iter, bench_iter_recursive);
 
 #[cfg(feature = "merge")]
-criterion_group!(bench_merge, bench_merge_recursive);
+criterion_group!(bench_merge, bench_merge_recursive, bench_merge_owned);
 
 #[cfg(feature = "sort")]
 criterion_group!(bench_sort, bench_sort_recursive);
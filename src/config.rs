@@ -0,0 +1,482 @@
+use crate::index::Path as IndexPath;
+use crate::iter::Traverser;
+use crate::merge::provenance::{union_all_with_provenance_by, Provenance};
+use crate::merge::subtree;
+use crate::merge::union_func;
+use serde_json::{Map, Value};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A configuration file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A JSON source could not be parsed.
+    Parse {
+        source_name: String,
+        source: serde_json::Error,
+    },
+    /// An environment variable or override does not name a valid key path.
+    InvalidKey { source_name: String, key: String },
+}
+
+impl std::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => {
+                write!(f, "failed to read {}: {source}", path.display())
+            }
+            Self::Parse {
+                source_name,
+                source,
+            } => write!(f, "failed to parse {source_name}: {source}"),
+            Self::InvalidKey { source_name, key } => {
+                write!(f, "invalid key {key:?} in {source_name}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source),
+            Self::InvalidKey { .. } => None,
+        }
+    }
+}
+
+/// Source of configuration values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// JSON file at the given path.
+    File(PathBuf),
+    /// Named JSON string.
+    Json { name: String, json: String },
+    /// Named value.
+    Value { name: String, value: Value },
+    /// Environment variables starting with `prefix`.
+    ///
+    /// The remainder of the variable name is lowercased and split into
+    /// a key path at `separator`, e.g. `APP_DB__HOST` becomes `db.host`
+    /// for prefix `APP_` and separator `__`.
+    /// If `vars` is `None`, the variables of the current process are used.
+    /// Variables that are not valid unicode are ignored.
+    Env {
+        prefix: String,
+        separator: String,
+        vars: Option<Vec<(OsString, OsString)>>,
+    },
+    /// Overrides in the form `key.path=value`.
+    Overrides(Vec<String>),
+}
+
+/// Layered configuration, merged from an ordered list of sources.
+///
+/// Later sources take precedence over earlier ones. Objects are merged
+/// recursively, while arrays and other values are replaced as a whole.
+/// Values of environment variables and overrides are coerced into
+/// booleans and numbers where possible and kept as strings otherwise.
+///
+/// ```
+/// use serde_json::json;
+/// use serde_json_merge::config::Config;
+/// use serde_json_merge::{index, Dfs};
+///
+/// let config = Config::new()
+///     .json("defaults", r#"{ "db": { "host": "localhost", "port": 5432 } }"#)
+///     .env_vars("APP_", [("APP_DB__PORT", "5433")])
+///     .set("db.user=admin")
+///     .load::<Dfs>()
+///     .unwrap();
+/// assert_eq!(
+///     config.merged,
+///     json!({ "db": { "host": "localhost", "port": 5433, "user": "admin" } })
+/// );
+/// assert_eq!(
+///     config.source(&index!("db", "port")).map(String::as_str),
+///     Some("env APP_DB__PORT")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    sources: Vec<Source>,
+}
+
+impl Config {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn source(mut self, source: Source) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Adds the JSON file at `path`.
+    #[inline]
+    #[must_use]
+    pub fn file(self, path: impl Into<PathBuf>) -> Self {
+        self.source(Source::File(path.into()))
+    }
+
+    /// Adds a named JSON string.
+    #[inline]
+    #[must_use]
+    pub fn json(self, name: impl Into<String>, json: impl Into<String>) -> Self {
+        self.source(Source::Json {
+            name: name.into(),
+            json: json.into(),
+        })
+    }
+
+    /// Adds a named value.
+    #[inline]
+    #[must_use]
+    pub fn value(self, name: impl Into<String>, value: Value) -> Self {
+        self.source(Source::Value {
+            name: name.into(),
+            value,
+        })
+    }
+
+    /// Adds the environment variables of the current process starting with `prefix`,
+    /// using `__` as the key path separator.
+    #[inline]
+    #[must_use]
+    pub fn env(self, prefix: impl Into<String>) -> Self {
+        self.source(Source::Env {
+            prefix: prefix.into(),
+            separator: "__".to_string(),
+            vars: None,
+        })
+    }
+
+    /// Adds the given environment variables starting with `prefix`,
+    /// using `__` as the key path separator.
+    #[inline]
+    #[must_use]
+    pub fn env_vars<K, V>(
+        self,
+        prefix: impl Into<String>,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Self
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        self.source(Source::Env {
+            prefix: prefix.into(),
+            separator: "__".to_string(),
+            vars: Some(
+                vars.into_iter()
+                    .map(|(key, value)| (key.into(), value.into()))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Adds a single `key.path=value` override.
+    #[inline]
+    #[must_use]
+    pub fn set(self, assignment: impl Into<String>) -> Self {
+        self.overrides([assignment])
+    }
+
+    /// Adds `key.path=value` overrides, e.g. from command line arguments.
+    #[inline]
+    #[must_use]
+    pub fn overrides(self, assignments: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.source(Source::Overrides(
+            assignments.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// Loads and merges all sources.
+    ///
+    /// Files are labeled `file <path>` and named sources by their name in the
+    /// provenance, while every environment variable and override is a layer of
+    /// its own labeled by its key, e.g. `env APP_DB__HOST` or `override db.host`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read, a JSON source cannot be parsed,
+    /// or an environment variable or override has an empty key path segment.
+    pub fn load<T>(&self) -> Result<Provenance<String>, Error>
+    where
+        T: Traverser,
+    {
+        let mut layers = Vec::new();
+        for source in &self.sources {
+            load_source(source, &mut layers)?;
+        }
+        // the union keeps existing values, hence the last layer is added first
+        layers.reverse();
+        Ok(union_all_with_provenance_by::<T, _, _, _>(
+            layers,
+            &mut |idx: &IndexPath, this: &mut Value, other: Option<&Value>| match (&*this, other) {
+                // arrays of lower layers are replaced instead of extended
+                (Value::Array(_), Some(Value::Array(_))) => false,
+                _ => union_func(idx, this, other),
+            },
+        ))
+    }
+}
+
+fn load_source(source: &Source, layers: &mut Vec<(String, Value)>) -> Result<(), Error> {
+    match source {
+        Source::File(path) => {
+            let name = path.display().to_string();
+            let json = read_file(path)?;
+            layers.push((format!("file {name}"), parse(&name, &json)?));
+        }
+        Source::Json { name, json } => layers.push((name.clone(), parse(name, json)?)),
+        Source::Value { name, value } => layers.push((name.clone(), value.clone())),
+        Source::Env {
+            prefix,
+            separator,
+            vars,
+        } => {
+            let vars: Vec<(OsString, OsString)> = match vars {
+                Some(vars) => vars.clone(),
+                None => std::env::vars_os().collect(),
+            };
+            // variables that are not valid unicode are ignored
+            let mut vars: Vec<(String, String)> = vars
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .filter(|(key, _)| key.starts_with(prefix.as_str()))
+                .collect();
+            vars.sort();
+            for (key, value) in vars {
+                let source_name = format!("env {key}");
+                let path = key[prefix.len()..]
+                    .split(separator.as_str())
+                    .map(str::to_lowercase);
+                let layer = nested(path, coerce(&value)).ok_or_else(|| Error::InvalidKey {
+                    source_name: source_name.clone(),
+                    key: key.clone(),
+                })?;
+                layers.push((source_name, layer));
+            }
+        }
+        Source::Overrides(assignments) => {
+            for assignment in assignments {
+                let invalid = || Error::InvalidKey {
+                    source_name: "overrides".to_string(),
+                    key: assignment.clone(),
+                };
+                let (key, value) = assignment.split_once('=').ok_or_else(invalid)?;
+                let key = key.trim();
+                let layer = nested(key.split('.').map(str::to_string), coerce(value.trim()))
+                    .ok_or_else(invalid)?;
+                layers.push((format!("override {key}"), layer));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn parse(name: &str, json: &str) -> Result<Value, Error> {
+    serde_json::from_str(json).map_err(|source| Error::Parse {
+        source_name: name.to_string(),
+        source,
+    })
+}
+
+/// Nests `value` into objects along `path`.
+///
+/// Returns `None` if `path` contains empty segments.
+fn nested(path: impl Iterator<Item = String>, value: Value) -> Option<Value> {
+    let idx: IndexPath = path
+        .map(|segment| (!segment.is_empty()).then_some(segment))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .fold(IndexPath::empty(), |mut idx, segment| {
+            idx.add(segment);
+            idx
+        });
+    let mut layer = Value::Object(Map::default());
+    // object keys never mismatch newly created objects
+    *subtree::get_or_insert(&mut layer, &idx).ok()? = value;
+    Some(layer)
+}
+
+/// Coerces a string into a boolean or number if possible.
+///
+/// Booleans are matched case insensitively. Integers that do not fit into
+/// 64 bits and non-finite floats are kept as strings.
+#[must_use]
+pub fn coerce(value: &str) -> Value {
+    if value.eq_ignore_ascii_case("true") {
+        return Value::Bool(true);
+    }
+    if value.eq_ignore_ascii_case("false") {
+        return Value::Bool(false);
+    }
+    if let Ok(number) = value.parse::<i64>() {
+        return Value::from(number);
+    }
+    if let Ok(number) = value.parse::<u64>() {
+        return Value::from(number);
+    }
+    let is_float = value
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+    if is_float {
+        if let Some(number) = value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Value::Number(number);
+        }
+    }
+    Value::String(value.to_string())
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{coerce, Config, Error};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn config_layers() {
+        let path = std::env::temp_dir().join(format!(
+            "serde_json_merge_config_{}.json",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"{ "db": { "host": "db.local" }, "hosts": ["a", "b"] }"#,
+        )
+        .unwrap();
+        let config = Config::new()
+            .json(
+                "defaults",
+                r#"{ "db": { "host": "localhost", "port": 5432 }, "hosts": ["x"], "debug": false }"#,
+            )
+            .file(&path)
+            .env_vars(
+                "APP_",
+                [
+                    ("APP_DB__PORT", "5433"),
+                    ("APP_DEBUG", "TRUE"),
+                    ("APP_LOG_LEVEL", "info"),
+                    ("OTHER_DB__PORT", "1"),
+                ],
+            )
+            .overrides(["db.port=6000", "ratio = 0.5"])
+            .load::<Dfs>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            config.merged,
+            json!({
+                "db": { "host": "db.local", "port": 6000 },
+                "hosts": ["a", "b"],
+                "debug": true,
+                "log_level": "info",
+                "ratio": 0.5,
+            })
+        );
+        let source = |idx| config.source(&idx).cloned();
+        assert_eq!(
+            source(index!("db", "host")),
+            Some(format!("file {}", path.display()))
+        );
+        assert_eq!(
+            source(index!("hosts", 0)),
+            Some(format!("file {}", path.display()))
+        );
+        assert_eq!(
+            source(index!("db", "port")),
+            Some("override db.port".into())
+        );
+        assert_eq!(source(index!("debug")), Some("env APP_DEBUG".into()));
+        assert_eq!(source(index!("ratio")), Some("override ratio".into()));
+    }
+
+    #[test]
+    fn config_errors() {
+        let missing = Config::new().file("/does/not/exist.json").load::<Dfs>();
+        assert!(matches!(missing, Err(Error::Io { .. })));
+
+        let invalid = Config::new().json("broken", "{").load::<Dfs>();
+        assert!(
+            matches!(invalid, Err(Error::Parse { ref source_name, .. }) if source_name == "broken")
+        );
+
+        for assignment in ["db.port", "db..port=1", "=1"] {
+            let result = Config::new().set(assignment).load::<Dfs>();
+            assert!(
+                matches!(result, Err(Error::InvalidKey { ref key, .. }) if key == assignment),
+                "{assignment}"
+            );
+        }
+        let result = Config::new()
+            .env_vars("APP_", [("APP_DB____PORT", "1")])
+            .load::<Dfs>();
+        assert!(matches!(result, Err(Error::InvalidKey { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn config_env_ignores_invalid_unicode() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let config = Config::new()
+            .env_vars(
+                "APP_",
+                [
+                    (OsStr::from_bytes(b"APP_VALID"), OsStr::from_bytes(b"1")),
+                    (
+                        OsStr::from_bytes(b"APP_INVALID"),
+                        OsStr::from_bytes(b"\xff\xfe"),
+                    ),
+                    (OsStr::from_bytes(b"APP_\xff"), OsStr::from_bytes(b"1")),
+                ],
+            )
+            .load::<Dfs>()
+            .unwrap();
+        assert_eq!(config.merged, json!({ "valid": 1 }));
+    }
+
+    #[test]
+    fn config_coerce() {
+        assert_eq!(coerce("true"), json!(true));
+        assert_eq!(coerce("False"), json!(false));
+        assert_eq!(coerce("-12"), json!(-12));
+        assert_eq!(coerce("18446744073709551615"), json!(u64::MAX));
+        assert_eq!(coerce("1.5e3"), json!(1500.0));
+        assert_eq!(coerce("NaN"), json!("NaN"));
+        assert_eq!(coerce("inf"), json!("inf"));
+        assert_eq!(coerce("1.2.3"), json!("1.2.3"));
+        assert_eq!(coerce("yes"), json!("yes"));
+        assert_eq!(coerce(""), json!(""));
+    }
+}
@@ -1,3 +1,4 @@
+use super::pointer;
 use super::utils;
 use fancy_regex::Regex;
 use serde_json::Value;
@@ -15,7 +16,7 @@ pub enum Kind<'a> {
 
 #[allow(clippy::module_name_repetitions)]
 pub trait JsonIndex: serde_json::value::Index + std::fmt::Display + std::fmt::Debug + Send {
-    fn kind(&self) -> Kind;
+    fn kind(&self) -> Kind<'_>;
 
     #[inline]
     fn eq(&self, other: &dyn JsonIndex) -> bool {
@@ -71,21 +72,21 @@ pub trait JsonIndex: serde_json::value::Index + std::fmt::Display + std::fmt::De
 
 impl JsonIndex for str {
     #[inline]
-    fn kind(&self) -> Kind {
+    fn kind(&self) -> Kind<'_> {
         Kind::ObjectKey(self)
     }
 }
 
 impl JsonIndex for String {
     #[inline]
-    fn kind(&self) -> Kind {
+    fn kind(&self) -> Kind<'_> {
         Kind::ObjectKey(self.as_str())
     }
 }
 
 impl JsonIndex for usize {
     #[inline]
-    fn kind(&self) -> Kind {
+    fn kind(&self) -> Kind<'_> {
         Kind::ArrayIndex(self)
     }
 }
@@ -96,7 +97,7 @@ where
     O: JsonIndex + Sized,
 {
     #[inline]
-    fn kind(&self) -> Kind {
+    fn kind(&self) -> Kind<'_> {
         JsonIndex::kind(*self)
     }
 }
@@ -152,6 +153,16 @@ impl std::fmt::Display for Path {
     }
 }
 
+impl std::str::FromStr for Path {
+    type Err = pointer::ParseError;
+
+    /// Parses a JSON pointer, see [`Path::from_pointer`].
+    #[inline]
+    fn from_str(pointer: &str) -> Result<Self, Self::Err> {
+        Self::from_pointer(pointer)
+    }
+}
+
 impl std::fmt::Debug for Path {
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -208,6 +219,33 @@ impl Path {
         Self::default()
     }
 
+    /// Parses an [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON pointer.
+    ///
+    /// All reference tokens are parsed as object keys, including the `-` token
+    /// for the end of an array, as whether a token refers to an array element
+    /// depends on the document. Use [`pointer::resolve`] to resolve them
+    /// against a document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pointer` is neither empty nor starts with `/`,
+    /// or contains a `~` that is not followed by `0` or `1`.
+    #[inline]
+    pub fn from_pointer(pointer: &str) -> Result<Self, pointer::ParseError> {
+        pointer::parse(pointer)
+    }
+
+    /// Formats the path as an [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901)
+    /// JSON pointer.
+    ///
+    /// Unlike the [`Display`](std::fmt::Display) output, the pointer escapes `~` and `/`
+    /// in object keys and can be parsed back using [`Path::from_pointer`].
+    #[inline]
+    #[must_use]
+    pub fn to_pointer(&self) -> String {
+        pointer::format(self)
+    }
+
     #[inline]
     #[must_use]
     pub fn join(mut self, other: &Path) -> Self {
@@ -223,7 +261,7 @@ impl Path {
     }
 
     #[inline]
-    pub fn iter(&self) -> std::slice::Iter<IndexRef> {
+    pub fn iter(&self) -> std::slice::Iter<'_, IndexRef> {
         self.0.iter()
     }
 
@@ -306,6 +344,12 @@ impl std::ops::IndexMut<Path> for Value {
 
 pub trait Index {
     type Value;
+
+    /// Returns the value at `path`.
+    ///
+    /// Paths starting with `/` are [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901)
+    /// JSON pointers, where `~1` escapes `/` and `~0` escapes `~`.
+    /// Other paths are split at `/` unless it is escaped as `\/`.
     fn get_path<S>(&self, path: S) -> Option<&Self::Value>
     where
         S: Borrow<str>;
@@ -423,7 +467,15 @@ impl Index for Value {
     where
         P: Borrow<str>,
     {
-        self.get_path_iter(split_path(path.borrow()))
+        let path = path.borrow();
+        if path.starts_with('/') {
+            let pointer = Path::from_pointer(path).ok()?;
+            return pointer.iter().try_fold(self, |val, index| match val {
+                Value::Array(values) => values.get(pointer::array_index(index)?),
+                val => val.get(pointer::object_key(index)),
+            });
+        }
+        self.get_path_iter(split_path(path))
     }
 
     #[inline]
@@ -431,7 +483,15 @@ impl Index for Value {
     where
         P: Borrow<str>,
     {
-        self.get_path_iter_mut(split_path(path.borrow()))
+        let path = path.borrow();
+        if path.starts_with('/') {
+            let pointer = Path::from_pointer(path).ok()?;
+            return pointer.iter().try_fold(self, |val, index| match val {
+                Value::Array(values) => values.get_mut(pointer::array_index(index)?),
+                val => val.get_mut(pointer::object_key(index)),
+            });
+        }
+        self.get_path_iter_mut(split_path(path))
     }
 }
 
@@ -465,7 +525,7 @@ macro_rules! index {
 
 #[cfg(test)]
 pub mod test {
-    use super::{is_integer, split_path, Index, Kind};
+    use super::{is_integer, split_path, Index, Kind, Path};
     use crate::test::ValueExt;
     use pretty_assertions::assert_eq;
     use serde_json::{json, Value};
@@ -639,6 +699,63 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn test_pointer_round_trip() {
+        let paths = [
+            index!(),
+            index!(""),
+            index!("a/b", "m~n", 0),
+            index!("~1", "-", " "),
+        ];
+        for path in paths {
+            let pointer = path.to_pointer();
+            assert_eq!(
+                pointer.parse::<Path>().map(|path| path.to_pointer()),
+                Ok(pointer)
+            );
+        }
+        assert_eq!(index!("a/b", 0).to_pointer(), "/a~1b/0");
+        assert_eq!(Path::from_pointer("/a~1b/0"), Ok(index!("a/b", "0")));
+        assert!("a/b".parse::<Path>().is_err());
+    }
+
+    #[test]
+    fn test_get_path_pointer() {
+        // example document from RFC 6901, section 5
+        let mut value = json!({
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8,
+        });
+        let expected = [
+            ("/foo", Some(json!(["bar", "baz"]))),
+            ("/foo/0", Some(json!("bar"))),
+            ("/foo/01", None),
+            ("/foo/-", None),
+            ("/", Some(json!(0))),
+            ("/a~1b", Some(json!(1))),
+            ("/c%d", Some(json!(2))),
+            ("/e^f", Some(json!(3))),
+            ("/g|h", Some(json!(4))),
+            ("/i\\j", Some(json!(5))),
+            ("/k\"l", Some(json!(6))),
+            ("/ ", Some(json!(7))),
+            ("/m~0n", Some(json!(8))),
+            ("/m~2n", None),
+        ];
+        for (pointer, expected) in expected {
+            assert_eq!(value.get_path(pointer), expected.as_ref(), "{pointer}");
+            assert_eq!(value.get_path_mut(pointer).cloned(), expected, "{pointer}");
+        }
+    }
+
     #[test]
     fn test_is_integer() {
         assert_eq!(is_integer(""), false);
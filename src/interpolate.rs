@@ -0,0 +1,471 @@
+use crate::index::{JsonIndex, Path as IndexPath};
+use crate::iter::{Iter, Traverser};
+use crate::pointer;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The placeholder at `path` is not terminated or contains an invalid JSON pointer.
+    InvalidPlaceholder {
+        path: IndexPath,
+        placeholder: String,
+    },
+    /// The variable `name` used at `path` is not defined and has no default.
+    UndefinedVariable { path: IndexPath, name: String },
+    /// The JSON pointer used at `path` does not refer to a value and has no default.
+    UnresolvedReference { path: IndexPath, pointer: String },
+    /// The JSON pointer used at `path` refers to a value that depends on itself.
+    Cycle { path: IndexPath, pointer: String },
+}
+
+impl std::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPlaceholder { path, placeholder } => {
+                write!(f, "invalid placeholder {placeholder:?} at {path}")
+            }
+            Self::UndefinedVariable { path, name } => {
+                write!(f, "undefined variable {name:?} at {path}")
+            }
+            Self::UnresolvedReference { path, pointer } => {
+                write!(f, "unresolved reference {pointer:?} at {path}")
+            }
+            Self::Cycle { path, pointer } => {
+                write!(f, "cyclic reference {pointer:?} at {path}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Resolves placeholders in all strings of `value`.
+///
+/// Placeholders have the form `${NAME}` for a variable in `vars` and `${/json/pointer}`
+/// for another value of the same document. Both may specify a default that is used
+/// if the variable is undefined or empty, or the pointer does not refer to a value,
+/// e.g. `${DB_HOST:-localhost}`. Use `$${` for a literal `${`.
+///
+/// A string consisting of a single reference is replaced by the referenced value,
+/// keeping its type. Otherwise, referenced strings are inserted as is and other
+/// values as JSON. Referenced values are interpolated before they are used.
+///
+/// ```
+/// use serde_json::json;
+/// use serde_json_merge::interpolate::interpolate;
+/// use serde_json_merge::Dfs;
+/// use std::collections::HashMap;
+///
+/// let mut config = json!({
+///     "defaults": { "port": 5432 },
+///     "db": { "host": "${DB_HOST:-localhost}", "port": "${/defaults/port}" },
+///     "url": "postgres://${/db/host}:${/db/port}",
+/// });
+/// let vars = HashMap::from([("DB_HOST".to_string(), "db.local".to_string())]);
+/// interpolate::<Dfs, _>(&mut config, &vars).unwrap();
+/// assert_eq!(config["db"], json!({ "host": "db.local", "port": 5432 }));
+/// assert_eq!(config["url"], json!("postgres://db.local:5432"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error naming the path of the first placeholder that cannot be resolved.
+/// In that case, `value` is left unchanged.
+pub fn interpolate<T, S>(value: &mut Value, vars: &HashMap<String, String, S>) -> Result<(), Error>
+where
+    T: Traverser,
+    S: BuildHasher,
+{
+    let document = value.clone();
+    let mut resolver = Resolver {
+        document: &document,
+        vars,
+        stack: Vec::new(),
+    };
+    let mut result = Ok(());
+    // values inserted by references are already interpolated
+    let mut inserted: Vec<IndexPath> = Vec::new();
+    let mut interpolated = value.clone();
+    interpolated
+        .mutate_recursive::<T>()
+        .for_each(|idx, value: &mut Value| {
+            if result.is_err() || inserted.iter().any(|path| idx.starts_with(path)) {
+                return;
+            }
+            if let Value::String(string) = value {
+                match resolver.interpolate_string(idx, string) {
+                    Ok(replacement) => {
+                        if replacement.is_object() || replacement.is_array() {
+                            inserted.push(idx.clone());
+                        }
+                        *value = replacement;
+                    }
+                    Err(err) => result = Err(err),
+                }
+            }
+        });
+    result?;
+    *value = interpolated;
+    Ok(())
+}
+
+/// Returns a copy of `value` with all placeholders resolved.
+///
+/// See [`interpolate`].
+///
+/// # Errors
+///
+/// Returns an error naming the path of the first placeholder that cannot be resolved.
+#[inline]
+pub fn interpolated<T, S>(value: &Value, vars: &HashMap<String, String, S>) -> Result<Value, Error>
+where
+    T: Traverser,
+    S: BuildHasher,
+{
+    let mut value = value.clone();
+    interpolate::<T, S>(&mut value, vars)?;
+    Ok(value)
+}
+
+/// Part of a string containing placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder {
+        name: &'a str,
+        default: Option<&'a str>,
+    },
+}
+
+/// Splits `string` into text and placeholders.
+///
+/// Returns the invalid placeholder if one is not terminated.
+fn parse(string: &str) -> Result<Vec<Part<'_>>, &str> {
+    let mut parts = Vec::new();
+    let mut rest = string;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // escaped using `$${`
+            parts.push(Part::Text(&rest[..start]));
+            parts.push(Part::Text("{"));
+            rest = &rest[start + 2..];
+            continue;
+        }
+        parts.push(Part::Text(&rest[..start]));
+        let placeholder = &rest[start..];
+        let end = placeholder.find('}').ok_or(placeholder)?;
+        let (name, default) = match placeholder[2..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&placeholder[2..end], None),
+        };
+        parts.push(Part::Placeholder { name, default });
+        rest = &placeholder[end + 1..];
+    }
+    parts.push(Part::Text(rest));
+    parts.retain(|part| *part != Part::Text(""));
+    Ok(parts)
+}
+
+struct Resolver<'a, S> {
+    document: &'a Value,
+    vars: &'a HashMap<String, String, S>,
+    /// Paths of the strings currently being interpolated.
+    stack: Vec<IndexPath>,
+}
+
+impl<'a, S> Resolver<'a, S>
+where
+    S: BuildHasher,
+{
+    fn interpolate_string(&mut self, idx: &IndexPath, string: &str) -> Result<Value, Error> {
+        let parts = parse(string).map_err(|placeholder| Error::InvalidPlaceholder {
+            path: idx.clone(),
+            placeholder: placeholder.to_string(),
+        })?;
+        if let [Part::Placeholder { name, default }] = parts.as_slice() {
+            if name.starts_with('/') {
+                self.stack.push(idx.clone());
+                let value = self.reference(idx, name, *default);
+                self.stack.pop();
+                return value;
+            }
+        }
+        if !parts
+            .iter()
+            .any(|part| matches!(part, Part::Placeholder { .. }))
+        {
+            return Ok(Value::String(concat_text(&parts)));
+        }
+        self.stack.push(idx.clone());
+        let interpolated = self.interpolate_parts(idx, &parts);
+        self.stack.pop();
+        interpolated.map(Value::String)
+    }
+
+    fn interpolate_parts(&mut self, idx: &IndexPath, parts: &[Part<'_>]) -> Result<String, Error> {
+        let mut interpolated = String::new();
+        for part in parts {
+            match *part {
+                Part::Text(text) => interpolated.push_str(text),
+                Part::Placeholder { name, default } if name.starts_with('/') => {
+                    match self.reference(idx, name, default)? {
+                        Value::String(string) => interpolated.push_str(&string),
+                        value => interpolated.push_str(&value.to_string()),
+                    }
+                }
+                Part::Placeholder { name, default } => match (self.vars.get(name), default) {
+                    (Some(value), Some(_)) if !value.is_empty() => {
+                        interpolated.push_str(value);
+                    }
+                    (Some(value), None) => interpolated.push_str(value),
+                    (_, Some(default)) => interpolated.push_str(default),
+                    (None, None) => {
+                        return Err(Error::UndefinedVariable {
+                            path: idx.clone(),
+                            name: name.to_string(),
+                        })
+                    }
+                },
+            }
+        }
+        Ok(interpolated)
+    }
+
+    /// Resolves the value referenced by `pointer` from the string at `idx`.
+    fn reference(
+        &mut self,
+        idx: &IndexPath,
+        pointer: &str,
+        default: Option<&str>,
+    ) -> Result<Value, Error> {
+        let invalid = || Error::InvalidPlaceholder {
+            path: idx.clone(),
+            placeholder: pointer.to_string(),
+        };
+        let tokens = IndexPath::from_pointer(pointer).map_err(|_| invalid())?;
+        let Some((target, value)) = self.lookup(&tokens) else {
+            return match default {
+                Some(default) => Ok(Value::String(default.to_string())),
+                None => Err(Error::UnresolvedReference {
+                    path: idx.clone(),
+                    pointer: pointer.to_string(),
+                }),
+            };
+        };
+        if self.stack.iter().any(|path| path.starts_with(&target)) {
+            return Err(Error::Cycle {
+                path: idx.clone(),
+                pointer: pointer.to_string(),
+            });
+        }
+        self.resolve(&target, value)
+    }
+
+    /// Finds the value referenced by the JSON pointer `tokens`,
+    /// together with its index path.
+    fn lookup(&self, tokens: &IndexPath) -> Option<(IndexPath, &'a Value)> {
+        let mut target = IndexPath::empty();
+        let mut value = self.document;
+        for token in tokens {
+            value = match value {
+                Value::Object(map) => {
+                    let key = pointer::object_key(token);
+                    let child = map.get(&key)?;
+                    target.add(key);
+                    child
+                }
+                Value::Array(values) => {
+                    let arr_idx = pointer::array_index(token)?;
+                    let child = values.get(arr_idx)?;
+                    target.add(arr_idx);
+                    child
+                }
+                _ => return None,
+            };
+        }
+        Some((target, value))
+    }
+
+    /// Interpolates all strings of `value`, which is located at `idx`.
+    fn resolve(&mut self, idx: &IndexPath, value: &Value) -> Result<Value, Error> {
+        match value {
+            Value::String(string) => self.interpolate_string(idx, string),
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .map(|(arr_idx, value)| self.resolve(&child(idx, arr_idx), value))
+                .collect(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, value)| {
+                    Ok((key.clone(), self.resolve(&child(idx, key.clone()), value)?))
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Object),
+            value => Ok(value.clone()),
+        }
+    }
+}
+
+fn child(idx: &IndexPath, index: impl JsonIndex + 'static) -> IndexPath {
+    let mut child = idx.clone();
+    child.add(index);
+    child
+}
+
+fn concat_text(parts: &[Part<'_>]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Text(text) => Some(*text),
+            Part::Placeholder { .. } => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{interpolate, interpolated, parse, Error, Part};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("HOST".to_string(), "example.com".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ])
+    }
+
+    #[test]
+    fn parse_placeholders() {
+        assert_eq!(
+            parse("a ${B:-c:-d} $${e} ${/f}"),
+            Ok(vec![
+                Part::Text("a "),
+                Part::Placeholder {
+                    name: "B",
+                    default: Some("c:-d"),
+                },
+                Part::Text(" $"),
+                Part::Text("{"),
+                Part::Text("e} "),
+                Part::Placeholder {
+                    name: "/f",
+                    default: None,
+                },
+            ])
+        );
+        assert_eq!(parse("a ${b"), Err("${b"));
+    }
+
+    #[test]
+    fn interpolate_vars() {
+        let value = json!({
+            "host": "${HOST}",
+            "empty": "${EMPTY}",
+            "defaults": ["${MISSING:-none}", "${EMPTY:-default}", "${HOST:-default}"],
+            "text": "https://${HOST}/$${HOST}",
+        });
+        assert_eq!(
+            interpolated::<Dfs, _>(&value, &vars()),
+            Ok(json!({
+                "host": "example.com",
+                "empty": "",
+                "defaults": ["none", "default", "example.com"],
+                "text": "https://example.com/${HOST}",
+            }))
+        );
+    }
+
+    #[test]
+    fn interpolate_references() {
+        let mut value = json!({
+            "server": { "host": "${HOST}", "port": 8080, "tls": true },
+            "copy": "${/server}",
+            "port": "${/server/port}",
+            "url": "http://${/server/host}:${/port}/${/list/1}",
+            "list": ["a", { "b": null }],
+            "fallback": "${/missing:-none}",
+            "a~b": "${/a~0b~1c:-escaped}",
+            "escaped": { "x": "$${/server}" },
+            "escaped_copy": "${/escaped}",
+        });
+        interpolate::<Dfs, _>(&mut value, &vars()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "server": { "host": "example.com", "port": 8080, "tls": true },
+                "copy": { "host": "example.com", "port": 8080, "tls": true },
+                "port": 8080,
+                "url": "http://example.com:8080/{\"b\":null}",
+                "list": ["a", { "b": null }],
+                "fallback": "none",
+                "a~b": "escaped",
+                "escaped": { "x": "${/server}" },
+                "escaped_copy": { "x": "${/server}" },
+            })
+        );
+    }
+
+    #[test]
+    fn interpolate_errors() {
+        let errors = [
+            (
+                json!({ "a": ["${MISSING}"] }),
+                Error::UndefinedVariable {
+                    path: index!("a", 0),
+                    name: "MISSING".into(),
+                },
+            ),
+            (
+                json!({ "a": "${/b}" }),
+                Error::UnresolvedReference {
+                    path: index!("a"),
+                    pointer: "/b".into(),
+                },
+            ),
+            (
+                json!({ "a": "x ${HOST" }),
+                Error::InvalidPlaceholder {
+                    path: index!("a"),
+                    placeholder: "${HOST".into(),
+                },
+            ),
+            (
+                json!({ "a": "${/~2}" }),
+                Error::InvalidPlaceholder {
+                    path: index!("a"),
+                    placeholder: "/~2".into(),
+                },
+            ),
+            (
+                json!({ "a": "${/b}", "b": "x${/c}", "c": "${/a}" }),
+                Error::Cycle {
+                    path: index!("b"),
+                    pointer: "/c".into(),
+                },
+            ),
+            (
+                json!({ "a": { "b": "${/a}" } }),
+                Error::Cycle {
+                    path: index!("a", "b"),
+                    pointer: "/a".into(),
+                },
+            ),
+        ];
+        for (value, expected) in errors {
+            let mut interpolated = value.clone();
+            assert_eq!(
+                interpolate::<Dfs, _>(&mut interpolated, &vars()),
+                Err(expected)
+            );
+            assert_eq!(interpolated, value);
+        }
+    }
+}
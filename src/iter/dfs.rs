@@ -1,4 +1,4 @@
-use super::{KeyValueIter, KeyValueMutator, Traverser};
+use super::{Control, KeyValueIter, KeyValueMutator, Traverser};
 use crate::{Index, IndexPath};
 use serde_json::Value;
 use std::collections::VecDeque;
@@ -115,11 +115,14 @@ impl Traverser for Dfs {
     }
 
     #[inline]
-    fn process_next(
+    fn process_next<C>(
         &mut self,
         root: &Value,
-        mut process: impl FnMut(&IndexPath, Option<&Value>) -> bool,
-    ) -> Option<IndexPath> {
+        mut process: impl FnMut(&IndexPath, Option<&Value>) -> C,
+    ) -> Option<IndexPath>
+    where
+        C: Into<Control>,
+    {
         match self.queue.pop_back() {
             Some((depth, index)) => {
                 // check if limit is reached
@@ -129,7 +132,21 @@ impl Traverser for Dfs {
                 }
 
                 let value = root.get_index(&index);
-                let proceed = process(&index, value);
+                let proceed = match process(&index, value).into() {
+                    Control::Descend => true,
+                    Control::Skip => false,
+                    Control::SkipSiblings => {
+                        // the remaining siblings are on top of the queue
+                        while self.queue.back().is_some_and(|(d, _)| *d == depth) {
+                            self.queue.pop_back();
+                        }
+                        false
+                    }
+                    Control::Stop => {
+                        self.queue.clear();
+                        false
+                    }
+                };
                 if proceed && self.depth.is_none_or(|d| depth < d) {
                     // add children
                     match value {
@@ -613,4 +630,88 @@ pub mod test {
             })
         );
     }
+
+    #[test]
+    fn process_next_control() {
+        use crate::iter::Control;
+
+        let value = json!({
+            "a": { "b": 1, "c": { "d": 2 } },
+            "e": [1, { "f": 3 }, 2],
+            "g": { "h": 4 },
+        });
+        let visit = |control: &dyn Fn(&IndexPath) -> Control| {
+            let mut visited = Vec::new();
+            let mut dfs = Dfs::new();
+            while dfs
+                .process_next(&value, |idx, _| {
+                    visited.push(idx.clone());
+                    control(idx)
+                })
+                .is_some()
+            {}
+            visited
+        };
+        assert_eq!(
+            visit(&|idx| match idx.to_string().as_str() {
+                "/a/c" | "/e/1" => Control::Skip,
+                _ => Control::Descend,
+            }),
+            vec![
+                index!(),
+                index!("a"),
+                index!("a", "b"),
+                index!("a", "c"),
+                index!("e"),
+                index!("e", 0),
+                index!("e", 1),
+                index!("e", 2),
+                index!("g"),
+                index!("g", "h"),
+            ]
+        );
+        assert_eq!(
+            visit(&|idx| match idx.to_string().as_str() {
+                "/a/b" | "/e/0" => Control::SkipSiblings,
+                _ => Control::Descend,
+            }),
+            vec![
+                index!(),
+                index!("a"),
+                index!("a", "b"),
+                index!("e"),
+                index!("e", 0),
+                index!("g"),
+                index!("g", "h"),
+            ]
+        );
+        assert_eq!(
+            visit(&|idx| if idx == &index!("e", 1) {
+                Control::Stop
+            } else {
+                Control::Descend
+            }),
+            vec![
+                index!(),
+                index!("a"),
+                index!("a", "b"),
+                index!("a", "c"),
+                index!("a", "c", "d"),
+                index!("e"),
+                index!("e", 0),
+                index!("e", 1),
+            ]
+        );
+        // returning bools still descends or skips
+        let mut dfs = Dfs::new();
+        let mut count = 0;
+        while dfs
+            .process_next(&value, |idx, _| {
+                count += 1;
+                idx.is_empty()
+            })
+            .is_some()
+        {}
+        assert_eq!(count, 4);
+    }
 }
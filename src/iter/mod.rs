@@ -7,6 +7,30 @@ pub trait ParallelTraverser: Sized {
     fn split(&mut self) -> Option<Self>;
 }
 
+/// Controls how a traversal proceeds after processing a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Continue with the children of the value.
+    Descend,
+    /// Do not visit the children of the value.
+    Skip,
+    /// Do not visit the children and the remaining siblings of the value.
+    SkipSiblings,
+    /// Stop the traversal.
+    Stop,
+}
+
+impl From<bool> for Control {
+    #[inline]
+    fn from(descend: bool) -> Self {
+        if descend {
+            Self::Descend
+        } else {
+            Self::Skip
+        }
+    }
+}
+
 pub trait Traverser {
     fn new() -> Self;
 
@@ -26,11 +50,15 @@ pub trait Traverser {
 
     fn next(&mut self, value: &Value) -> Option<IndexPath>;
 
-    fn process_next(
+    /// Processes the next value, where `process` returns a [`Control`]
+    /// (or a `bool` indicating if the children should be visited).
+    fn process_next<C>(
         &mut self,
         value: &Value,
-        process: impl FnMut(&IndexPath, Option<&Value>) -> bool,
-    ) -> Option<IndexPath>;
+        process: impl FnMut(&IndexPath, Option<&Value>) -> C,
+    ) -> Option<IndexPath>
+    where
+        C: Into<Control>;
 
     fn reset(&mut self);
 }
@@ -114,25 +142,25 @@ where
 }
 
 pub trait Iter {
-    fn iter<T>(&self) -> KeyValueIter<T>
+    fn iter<T>(&self) -> KeyValueIter<'_, T>
     where
         T: Traverser;
 
-    fn mutate<T>(&mut self) -> KeyValueMutator<T>
+    fn mutate<T>(&mut self) -> KeyValueMutator<'_, T>
     where
         T: Traverser;
 
-    fn iter_recursive<T>(&self) -> KeyValueIter<T>
+    fn iter_recursive<T>(&self) -> KeyValueIter<'_, T>
     where
         T: Traverser;
 
-    fn mutate_recursive<T>(&mut self) -> KeyValueMutator<T>
+    fn mutate_recursive<T>(&mut self) -> KeyValueMutator<'_, T>
     where
         T: Traverser;
 }
 
 impl Iter for Value {
-    fn iter<T>(&self) -> KeyValueIter<T>
+    fn iter<T>(&self) -> KeyValueIter<'_, T>
     where
         T: Traverser,
     {
@@ -145,7 +173,7 @@ impl Iter for Value {
         }
     }
 
-    fn mutate<T>(&mut self) -> KeyValueMutator<T>
+    fn mutate<T>(&mut self) -> KeyValueMutator<'_, T>
     where
         T: Traverser,
     {
@@ -158,7 +186,7 @@ impl Iter for Value {
         }
     }
 
-    fn iter_recursive<T>(&self) -> KeyValueIter<T>
+    fn iter_recursive<T>(&self) -> KeyValueIter<'_, T>
     where
         T: Traverser,
     {
@@ -171,7 +199,7 @@ impl Iter for Value {
         }
     }
 
-    fn mutate_recursive<T>(&mut self) -> KeyValueMutator<T>
+    fn mutate_recursive<T>(&mut self) -> KeyValueMutator<'_, T>
     where
         T: Traverser,
     {
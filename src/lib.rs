@@ -1,7 +1,16 @@
+#[cfg(feature = "config")]
+pub mod config;
 pub mod index;
+#[cfg(feature = "interpolate")]
+pub mod interpolate;
 pub mod iter;
 #[cfg(feature = "merge")]
 pub mod merge;
+#[cfg(feature = "patch")]
+pub mod patch;
+pub mod pointer;
+#[cfg(feature = "query")]
+pub mod query;
 #[cfg(feature = "sort")]
 pub mod sort;
 #[cfg(test)]
@@ -12,6 +21,10 @@ pub use index::{Index, IndexRef, Path as IndexPath};
 pub use iter::dfs::{Dfs, Iter as DfsIter};
 pub use iter::Iter;
 #[cfg(feature = "merge")]
-pub use merge::{Merge, Union};
+pub use merge::{Merge, MergeControl, Union};
+#[cfg(feature = "patch")]
+pub use patch::Patch;
+#[cfg(feature = "query")]
+pub use query::{JsonPath, Query};
 #[cfg(feature = "sort")]
 pub use sort::{Keys as SortKeys, Sort, ValueOrd, Values as SortValues};
@@ -0,0 +1,373 @@
+use super::Merge;
+use crate::index::{Index, Path as IndexPath};
+use crate::iter::Traverser;
+use serde_json::{Map, Value};
+
+/// Merge directives embedded in the patch document,
+/// similar to Kubernetes strategic merge patches.
+///
+/// With the default `$` prefix, the following directives are supported:
+/// - `"$patch": "replace"` in an object replaces the target object
+///   instead of merging into it
+/// - `"$patch": "delete"` in an object deletes the target object
+/// - `"$retainKeys": ["a", "b"]` in an object removes all other keys from the target object
+/// - `"$setElementOrder/list": [..]` in an object reorders the elements of the array
+///   at `list` after merging. Objects in the order match elements containing
+///   the same fields, other values match equal elements. Elements missing from
+///   the order are kept after the ordered ones.
+/// - `{"$patch": "replace"}` as an array element replaces the target array
+///   with the other elements instead of extending it
+/// - `{"$patch": "delete", "name": "a"}` as an array element deletes all
+///   elements of the target array containing the same fields. Without
+///   other fields, only empty objects are deleted.
+///
+/// Directives are stripped from the values copied from the patch. Values of
+/// the target are kept as they are, even if they look like directives.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Directives {
+    prefix: String,
+}
+
+impl Default for Directives {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            prefix: "$".to_string(),
+        }
+    }
+}
+
+/// Action of a `$patch` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatchAction {
+    Merge,
+    Replace,
+    Delete,
+}
+
+impl Directives {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prefix of directive keys, which defaults to `$`.
+    #[inline]
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    /// Checks if `key` is the key of a directive.
+    #[must_use]
+    pub fn is_directive(&self, key: &str) -> bool {
+        key.strip_prefix(&self.prefix).is_some_and(|name| {
+            matches!(name, "patch" | "retainKeys") || name.starts_with("setElementOrder/")
+        })
+    }
+
+    fn patch_action(&self, value: &Value) -> PatchAction {
+        match value.get(self.key("patch")).and_then(Value::as_str) {
+            Some("replace") => PatchAction::Replace,
+            Some("delete") => PatchAction::Delete,
+            _ => PatchAction::Merge,
+        }
+    }
+
+    /// Checks if `element` is matched by `pattern`, ignoring the directives in `pattern`.
+    fn matches(&self, pattern: &Value, element: &Value) -> bool {
+        match (pattern, element) {
+            (Value::Object(pattern), Value::Object(element)) => {
+                let mut fields = pattern
+                    .iter()
+                    .filter(|(key, _)| !self.is_directive(key))
+                    .peekable();
+                if fields.peek().is_none() {
+                    // a pattern without fields only matches empty objects
+                    return element.is_empty();
+                }
+                fields.all(|(key, value)| element.get(key) == Some(value))
+            }
+            (pattern, element) => pattern == element,
+        }
+    }
+
+    /// Removes all directives from `value`.
+    pub fn strip(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|key, _| !self.is_directive(key));
+                for value in map.values_mut() {
+                    self.strip(value);
+                }
+            }
+            Value::Array(values) => {
+                values.retain(|value| self.patch_action(value) == PatchAction::Merge);
+                for value in values {
+                    self.strip(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn stripped(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        self.strip(&mut value);
+        value
+    }
+
+    /// Merges `patch` into `this`, following the directives in `patch`.
+    pub(crate) fn merge<T>(&self, this: &mut Value, patch: &Value)
+    where
+        T: Traverser,
+    {
+        if self.patch_action(patch) == PatchAction::Delete {
+            *this = Value::Null;
+            return;
+        }
+        let mut orders: Vec<(IndexPath, Vec<Value>)> = Vec::new();
+        this.merge_by_recursive::<T, _>(patch, &mut |idx, this, other| {
+            if idx
+                .last()
+                .and_then(|index| index.try_as_object_key())
+                .is_some_and(|key| self.is_directive(key))
+            {
+                return false;
+            }
+            match (this, other) {
+                (Value::Object(this), Some(Value::Object(other))) => {
+                    self.merge_object(idx, this, other, &mut orders)
+                }
+                (Value::Array(this), Some(Value::Array(other))) => {
+                    self.merge_array(this, other);
+                    false
+                }
+                (this, other) => {
+                    // only values copied from the patch contain directives
+                    let other = other.map(|other| self.stripped(other));
+                    super::merge_func(idx, this, other.as_ref())
+                }
+            }
+        });
+        for (idx, order) in orders {
+            if let Some(Value::Array(values)) = this.get_index_mut(&idx) {
+                self.set_element_order(values, &order);
+            }
+        }
+    }
+
+    fn merge_object(
+        &self,
+        idx: &IndexPath,
+        this: &mut Map<String, Value>,
+        other: &Map<String, Value>,
+        orders: &mut Vec<(IndexPath, Vec<Value>)>,
+    ) -> bool {
+        if other.get(&self.key("patch")).and_then(Value::as_str) == Some("replace") {
+            let mut replacement = other.clone();
+            replacement.retain(|key, _| !self.is_directive(key));
+            for value in replacement.values_mut() {
+                self.strip(value);
+            }
+            *this = replacement;
+            return false;
+        }
+        if let Some(Value::Array(retain)) = other.get(&self.key("retainKeys")) {
+            this.retain(|key, _| retain.iter().any(|retained| retained.as_str() == Some(key)));
+        }
+        let order_prefix = self.key("setElementOrder/");
+        for (key, value) in other {
+            if let Some(list) = key.strip_prefix(&order_prefix) {
+                if let Value::Array(order) = value {
+                    let mut list_idx = idx.clone();
+                    list_idx.add(list.to_string());
+                    orders.push((list_idx, order.clone()));
+                }
+            } else if self.is_directive(key) {
+                // directives are not merged
+            } else if self.patch_action(value) == PatchAction::Delete {
+                this.shift_remove(key);
+            } else {
+                this.entry(key.clone()).or_insert(Value::Null);
+            }
+        }
+        true
+    }
+
+    fn merge_array(&self, this: &mut Vec<Value>, other: &[Value]) {
+        if other
+            .iter()
+            .any(|value| self.patch_action(value) == PatchAction::Replace)
+        {
+            this.clear();
+        }
+        for value in other {
+            match self.patch_action(value) {
+                PatchAction::Delete => this.retain(|element| !self.matches(value, element)),
+                PatchAction::Replace => {}
+                PatchAction::Merge => this.push(self.stripped(value)),
+            }
+        }
+    }
+
+    fn set_element_order(&self, values: &mut Vec<Value>, order: &[Value]) {
+        let mut remaining = std::mem::take(values);
+        for pattern in order {
+            let (matched, rest) = remaining
+                .into_iter()
+                .partition(|element| self.matches(pattern, element));
+            values.extend::<Vec<Value>>(matched);
+            remaining = rest;
+        }
+        values.extend(remaining);
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::Directives;
+    use crate::iter::dfs::Dfs;
+    use crate::Merge;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn directive_patch_replace() {
+        let base = json!({
+            "spec": { "a": 1, "b": { "c": 2 } },
+            "list": [1, 2],
+        });
+        let patch = json!({
+            "spec": { "$patch": "replace", "b": { "d": 3, "$retainKeys": [] } },
+            "list": [{ "$patch": "replace" }, 3],
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({
+                "spec": { "b": { "d": 3 } },
+                "list": [3],
+            })
+        );
+    }
+
+    #[test]
+    fn directive_patch_delete() {
+        let base = json!({
+            "spec": { "a": 1 },
+            "containers": [
+                { "name": "web", "image": "web:1" },
+                { "name": "sidecar", "image": "proxy:1" },
+            ],
+        });
+        let patch = json!({
+            "spec": { "$patch": "delete" },
+            "missing": { "$patch": "delete" },
+            "containers": [
+                { "$patch": "delete", "name": "sidecar" },
+                { "name": "db", "image": "db:1" },
+            ],
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({
+                "containers": [
+                    { "name": "web", "image": "web:1" },
+                    { "name": "db", "image": "db:1" },
+                ],
+            })
+        );
+        assert_eq!(
+            json!({"a": 1})
+                .merged_directives::<Dfs>(&json!({"$patch": "delete"}), &Directives::new()),
+            json!(null)
+        );
+    }
+
+    #[test]
+    fn directive_bare_delete_element() {
+        let base = json!({ "list": [{ "name": "a" }, {}, 1] });
+        let patch = json!({ "list": [{ "$patch": "delete" }] });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({ "list": [{ "name": "a" }, 1] })
+        );
+    }
+
+    #[test]
+    fn directive_keys_in_base_are_kept() {
+        let base = json!({
+            "kept": { "$retainKeys": ["x"], "x": 1 },
+            "list": [{ "$patch": "delete", "name": "a" }],
+            "values": [{ "$patch": "replace" }],
+        });
+        let patch = json!({
+            "new": { "$patch": "merge", "y": 2 },
+            "values": 1,
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({
+                "kept": { "$retainKeys": ["x"], "x": 1 },
+                "list": [{ "$patch": "delete", "name": "a" }],
+                "values": [{ "$patch": "replace" }, 1],
+                "new": { "y": 2 },
+            })
+        );
+    }
+
+    #[test]
+    fn directive_retain_keys() {
+        let base = json!({
+            "strategy": { "type": "RollingUpdate", "rollingUpdate": { "maxSurge": 1 } },
+        });
+        let patch = json!({
+            "strategy": { "$retainKeys": ["type"], "type": "Recreate" },
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({ "strategy": { "type": "Recreate" } })
+        );
+    }
+
+    #[test]
+    fn directive_set_element_order() {
+        let base = json!({
+            "ports": [80, 443, 8080],
+            "containers": [{ "name": "a" }, { "name": "b" }],
+        });
+        let patch = json!({
+            "$setElementOrder/ports": [8080, 9090, 80],
+            "ports": [9090],
+            "$setElementOrder/containers": [{ "name": "c" }, { "name": "a" }],
+            "containers": [{ "name": "c", "$retainKeys": ["name"] }],
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new()),
+            json!({
+                "ports": [8080, 9090, 80, 443],
+                "containers": [{ "name": "c" }, { "name": "a" }, { "name": "b" }],
+            })
+        );
+    }
+
+    #[test]
+    fn directive_custom_prefix() {
+        let base = json!({ "a": { "b": 1 }, "$patch": "data" });
+        let patch = json!({
+            "a": { "@@patch": "replace", "c": 2 },
+            "$patch": "value",
+            "new": { "@@retainKeys": [], "d": { "@@patch": "merge" } },
+        });
+        assert_eq!(
+            base.merged_directives::<Dfs>(&patch, &Directives::new().prefix("@@")),
+            json!({ "a": { "c": 2 }, "$patch": "value", "new": { "d": {} } })
+        );
+    }
+}
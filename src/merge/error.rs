@@ -0,0 +1,90 @@
+use crate::index::Path as IndexPath;
+use crate::pointer;
+use serde_json::Value;
+
+/// Kind of a JSON value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl From<&Value> for ValueKind {
+    #[inline]
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Bool,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+            Value::Array(_) => Self::Array,
+            Value::Object(_) => Self::Object,
+        }
+    }
+}
+
+impl std::fmt::Display for ValueKind {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The values at `path` are of different kinds.
+    TypeMismatch {
+        path: IndexPath,
+        this: ValueKind,
+        other: ValueKind,
+    },
+    /// The array at `path` has no element at `index`,
+    /// which is neither an existing index nor the index after the last element.
+    IndexOutOfRange {
+        path: IndexPath,
+        index: usize,
+        len: usize,
+    },
+    /// A path starting with `/` is not a valid JSON pointer.
+    InvalidPointer(pointer::ParseError),
+}
+
+impl std::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TypeMismatch { path, this, other } => {
+                write!(f, "cannot merge {other} into {this} at {path}")
+            }
+            Self::IndexOutOfRange { path, index, len } => {
+                write!(
+                    f,
+                    "index {index} is out of range for array of length {len} at {path}"
+                )
+            }
+            Self::InvalidPointer(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPointer(err) => Some(err),
+            Self::TypeMismatch { .. } | Self::IndexOutOfRange { .. } => None,
+        }
+    }
+}
@@ -1,11 +1,91 @@
+pub mod directives;
+pub mod error;
+pub mod options;
+pub mod patch;
+pub mod pattern;
+pub mod provenance;
+pub mod rules;
+pub mod set;
+pub(crate) mod subtree;
+pub mod three_way;
+pub mod tombstone;
+#[cfg(feature = "typed")]
+pub mod typed;
 pub mod union;
 
 use crate::index::{Index, Path as IndexPath};
+pub use crate::iter::Control as MergeControl;
 use crate::iter::Traverser;
-use serde_json::Value;
-pub use union::Union;
+pub use directives::Directives;
+pub use error::{Error, ValueKind};
+pub use options::{ArrayStrategy, ElementKey, MergeOptions, NullPolicy};
+pub use patch::create_merge_patch;
+pub use pattern::PathPattern;
+pub use provenance::{union_all_with_provenance, Provenance};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+pub use rules::Rule;
+use serde_json::{Map, Value};
+pub use set::{ArraySemantics, Difference, Intersect, SetOptions};
+use std::borrow::{Borrow, BorrowMut};
+use std::convert::Infallible;
+pub use three_way::{merge_three_way, Resolution};
+pub use tombstone::Tombstone;
+#[cfg(feature = "typed")]
+pub use typed::merge_typed;
+pub use union::{union_func, Union};
+
+/// Policy for values at the depth limit of [`Merge::merge_to_depth`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Cutoff {
+    /// Replace the value with the other value, unless the other value is `null`.
+    #[default]
+    Replace,
+    /// Keep the value and only add the other value if it is missing.
+    KeepBase,
+    /// Always replace the value with a deep clone of the other value,
+    /// even if the other value is `null`.
+    CloneOther,
+}
 
 pub trait Merge: Sized {
+    /// Recursively merges all values into an empty object.
+    #[inline]
+    #[must_use]
+    fn merge_all<T, V>(values: impl IntoIterator<Item = V>) -> Self
+    where
+        T: Traverser,
+        V: Borrow<Value>,
+        Self: From<Value>,
+    {
+        let mut result = Value::Object(Map::default());
+        for v in values {
+            result.merge_recursive::<T>(v.borrow());
+        }
+        Self::from(result)
+    }
+
+    /// Recursively merges all values in parallel by reducing partial merges.
+    ///
+    /// The result is the same as for [`Merge::merge_all`] if the values at each
+    /// path are either all arrays, all objects or all neither.
+    /// Otherwise, it depends on how the values are split between threads,
+    /// because merging is not associative: merging `"x"` and then `"y"` into
+    /// `[1]` gives `[1, "x", "y"]`, but merging `"x"` and `"y"` first gives `[1, "y"]`.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    #[must_use]
+    fn par_merge_all<T, I, V>(values: I) -> Self
+    where
+        T: Traverser,
+        I: IntoParallelIterator<Item = V>,
+        I::Iter: IndexedParallelIterator,
+        V: Borrow<Value>,
+        Self: From<Value>,
+    {
+        Self::from(par_reduce::<T, I, V, _>(values, &merge_func))
+    }
+
     fn merge<T>(&mut self, other: &Self)
     where
         T: Traverser;
@@ -24,6 +104,306 @@ pub trait Merge: Sized {
         T: Traverser,
         F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool;
 
+    /// Merges `other` like [`Merge::merge_by`], where `merge` returns a [`MergeControl`]
+    /// to skip the children or siblings of a value or to stop merging.
+    #[inline]
+    fn merge_by_control<T, F>(&mut self, other: &Self, merge: &mut F)
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> MergeControl,
+        Self: BorrowMut<Value>,
+    {
+        self.try_merge_by::<T, _, Infallible>(other, &mut |idx, this, other| {
+            Ok(merge(idx, this, other))
+        })
+        .unwrap_or_else(|err| match err {});
+    }
+
+    /// Merges `other` like [`Merge::merge_by_recursive`], where `merge` returns a
+    /// [`MergeControl`] to skip the children or siblings of a value or to stop merging.
+    #[inline]
+    fn merge_by_recursive_control<T, F>(&mut self, other: &Self, merge: &mut F)
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> MergeControl,
+        Self: BorrowMut<Value>,
+    {
+        self.try_merge_by_recursive::<T, _, Infallible>(other, &mut |idx, this, other| {
+            Ok(merge(idx, this, other))
+        })
+        .unwrap_or_else(|err| match err {});
+    }
+
+    /// Merges `other` using a fallible merge function.
+    ///
+    /// # Errors
+    ///
+    /// Stops merging and returns the first error returned by `merge`.
+    /// Values merged before the error occurred remain merged.
+    #[inline]
+    fn try_merge_by<T, F, E>(&mut self, other: &Self, merge: &mut F) -> Result<(), E>
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> Result<MergeControl, E>,
+        Self: BorrowMut<Value>,
+    {
+        try_merge_by_depth::<T, F, E>(self.borrow_mut(), other.borrow(), Some(1), merge)
+    }
+
+    /// Recursively merges `other` using a fallible merge function.
+    ///
+    /// # Errors
+    ///
+    /// Stops merging and returns the first error returned by `merge`.
+    /// Values merged before the error occurred remain merged.
+    #[inline]
+    fn try_merge_by_recursive<T, F, E>(&mut self, other: &Self, merge: &mut F) -> Result<(), E>
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> Result<MergeControl, E>,
+        Self: BorrowMut<Value>,
+    {
+        try_merge_by_depth::<T, F, E>(self.borrow_mut(), other.borrow(), None, merge)
+    }
+
+    /// Merges `other` like [`Merge::merge`] but fails on type mismatches.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if the values at a path are of
+    /// different kinds, except when either of them is `null`.
+    #[inline]
+    fn try_merge<T>(&mut self, other: &Self) -> Result<(), Error>
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.try_merge_by::<T, _, _>(other, &mut strict_merge_func)
+    }
+
+    /// Merges `other` like [`Merge::merge_recursive`] but fails on type mismatches.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if the values at a path are of
+    /// different kinds, except when either of them is `null`.
+    #[inline]
+    fn try_merge_recursive<T>(&mut self, other: &Self) -> Result<(), Error>
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.try_merge_by_recursive::<T, _, _>(other, &mut strict_merge_func)
+    }
+
+    /// Merges `other` like [`Merge::merge_recursive`], but moves its values into
+    /// place instead of cloning them.
+    #[inline]
+    fn merge_owned(&mut self, other: Self)
+    where
+        Self: BorrowMut<Value> + Into<Value>,
+    {
+        merge_owned_func(self.borrow_mut(), other.into());
+    }
+
+    #[inline]
+    #[must_use]
+    fn merged_owned(mut self, other: Self) -> Self
+    where
+        Self: BorrowMut<Value> + Into<Value>,
+    {
+        self.merge_owned(other);
+        self
+    }
+
+    /// Merges `other` down to `depth`, where the root value has depth 0.
+    ///
+    /// Values above `depth` are merged like [`Merge::merge_recursive`],
+    /// while values at `depth` are not merged but handled according to `cutoff`.
+    /// For example, a depth of 2 merges the top two levels of objects
+    /// and replaces their values.
+    #[inline]
+    fn merge_to_depth<T>(&mut self, other: &Self, depth: usize, cutoff: Cutoff)
+    where
+        T: Traverser,
+    {
+        self.merge_by_recursive::<T, _>(other, &mut |idx, this, other| {
+            if idx.depth() < depth {
+                return merge_func(idx, this, other);
+            }
+            match (cutoff, other) {
+                (Cutoff::Replace, Some(other)) if !other.is_null() => this.clone_from(other),
+                (Cutoff::KeepBase, Some(other)) if this.is_null() => this.clone_from(other),
+                (Cutoff::CloneOther, Some(other)) => this.clone_from(other),
+                _ => {}
+            }
+            false
+        });
+    }
+
+    #[inline]
+    #[must_use]
+    fn merged_to_depth<T>(mut self, other: &Self, depth: usize, cutoff: Cutoff) -> Self
+    where
+        T: Traverser,
+    {
+        self.merge_to_depth::<T>(other, depth, cutoff);
+        self
+    }
+
+    /// Recursively merges `other` into the value at `idx`.
+    ///
+    /// Missing or `null` values along `idx` are created as objects or arrays,
+    /// depending on whether the next index is an object key or an array index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if a value along `idx` is neither
+    /// `null` nor of the kind required by the next index.
+    #[inline]
+    fn merge_index<T>(&mut self, idx: &IndexPath, other: &Self) -> Result<(), Error>
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.merge_index_by::<T, _>(idx, other, &mut merge_func)
+    }
+
+    /// Merges `other` into the value at `idx` using a custom merge function.
+    ///
+    /// The merge function receives the full index paths, starting with `idx`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if a value along `idx` is neither
+    /// `null` nor of the kind required by the next index.
+    #[inline]
+    fn merge_index_by<T, F>(
+        &mut self,
+        idx: &IndexPath,
+        other: &Self,
+        merge: &mut F,
+    ) -> Result<(), Error>
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool,
+        Self: BorrowMut<Value>,
+    {
+        let subtree = subtree::get_or_insert(self.borrow_mut(), idx)?;
+        subtree.merge_by_recursive::<T, _>(other.borrow(), &mut |sub_idx, this, other| {
+            merge(&idx.clone().join(sub_idx), this, other)
+        });
+        Ok(())
+    }
+
+    /// Recursively merges `other` into the value at the `/` separated `path`.
+    ///
+    /// Path segments are resolved like in [`Index::get_path`], where paths
+    /// starting with `/` are JSON pointers and integer segments are array
+    /// indices if they refer to an existing array.
+    /// Missing values are created as objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if a value along `path` is neither
+    /// `null` nor of the kind required by the next segment, and
+    /// [`Error::InvalidPointer`] if `path` is not a valid JSON pointer.
+    #[inline]
+    fn merge_at<T>(&mut self, path: &str, other: &Self) -> Result<(), Error>
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.merge_at_by::<T, _>(path, other, &mut merge_func)
+    }
+
+    /// Merges `other` into the value at the `/` separated `path`
+    /// using a custom merge function.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] if a value along `path` is neither
+    /// `null` nor of the kind required by the next segment, and
+    /// [`Error::InvalidPointer`] if `path` is not a valid JSON pointer.
+    #[inline]
+    fn merge_at_by<T, F>(&mut self, path: &str, other: &Self, merge: &mut F) -> Result<(), Error>
+    where
+        T: Traverser,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool,
+        Self: BorrowMut<Value>,
+    {
+        let idx = subtree::resolve_path((*self).borrow(), path)?;
+        self.merge_index_by::<T, F>(&idx, other, merge)
+    }
+
+    /// Applies `patch` as a JSON Merge Patch according to
+    /// [RFC 7396](https://datatracker.ietf.org/doc/html/rfc7396).
+    ///
+    /// Unlike [`Merge::merge_recursive`], `null` values in the patch remove
+    /// the corresponding key and arrays are replaced instead of extended.
+    #[inline]
+    fn merge_patch<T>(&mut self, patch: &Self)
+    where
+        T: Traverser,
+    {
+        self.merge_by_recursive::<T, _>(patch, &mut patch::merge_patch_func);
+    }
+
+    /// Merges `other` recursively, using `options` to decide how arrays are merged
+    /// and which [`Rule`]s apply to which paths.
+    #[inline]
+    fn merge_with<T>(&mut self, other: &Self, options: &MergeOptions)
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.merge_by_recursive_control::<T, _>(other, &mut |idx, this, other| {
+            options.merge_func::<T>(idx, this, other)
+        });
+    }
+
+    /// Merges `patch` recursively, following the [`Directives`] embedded in it.
+    #[inline]
+    fn merge_directives<T>(&mut self, patch: &Self, directives: &Directives)
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        directives.merge::<T>(self.borrow_mut(), patch.borrow());
+    }
+
+    #[inline]
+    #[must_use]
+    fn merged_directives<T>(mut self, patch: &Self, directives: &Directives) -> Self
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.merge_directives::<T>(patch, directives);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    fn merged_with<T>(mut self, other: &Self, options: &MergeOptions) -> Self
+    where
+        T: Traverser,
+        Self: BorrowMut<Value>,
+    {
+        self.merge_with::<T>(other, options);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    fn merged_patch<T>(mut self, patch: &Self) -> Self
+    where
+        T: Traverser,
+    {
+        self.merge_patch::<T>(patch);
+        self
+    }
+
     #[inline]
     #[must_use]
     fn merged<T>(mut self, other: &Self) -> Self
@@ -127,7 +507,114 @@ impl Merge for Value {
     }
 }
 
-fn merge_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool {
+/// Merges all values into an empty object using a tree reduction.
+///
+/// Each thread merges a contiguous chunk of the values before the partial
+/// results are merged in order, which gives the same result as merging
+/// sequentially if `merge` is associative.
+#[cfg(feature = "rayon")]
+fn par_reduce<T, I, V, F>(values: I, merge: &F) -> Value
+where
+    T: Traverser,
+    I: IntoParallelIterator<Item = V>,
+    I::Iter: IndexedParallelIterator,
+    V: Borrow<Value>,
+    F: Fn(&IndexPath, &mut Value, Option<&Value>) -> bool + Sync,
+{
+    let empty = || Value::Object(Map::default());
+    values
+        .into_par_iter()
+        .fold(empty, |mut result, value| {
+            result.merge_by_recursive::<T, _>(value.borrow(), &mut |idx, this, other| {
+                merge(idx, this, other)
+            });
+            result
+        })
+        .reduce(empty, |mut result, partial| {
+            result.merge_by_recursive::<T, _>(&partial, &mut |idx, this, other| {
+                merge(idx, this, other)
+            });
+            result
+        })
+}
+
+#[inline]
+fn try_merge_by_depth<T, F, E>(
+    this: &mut Value,
+    other: &Value,
+    depth: Option<usize>,
+    merge: &mut F,
+) -> Result<(), E>
+where
+    T: Traverser,
+    F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> Result<MergeControl, E>,
+{
+    let mut result = Ok(());
+    let mut traverser = T::new();
+    traverser.set_limit(None);
+    traverser.set_depth(depth);
+    while result.is_ok()
+        && traverser
+            .process_next(other, |idx, new_value| {
+                let Some(value) = this.get_index_mut(idx) else {
+                    return MergeControl::Descend;
+                };
+                merge(idx, value, new_value).unwrap_or_else(|err| {
+                    result = Err(err);
+                    MergeControl::Stop
+                })
+            })
+            .is_some()
+    {}
+    result
+}
+
+fn strict_merge_func(
+    idx: &IndexPath,
+    this: &mut Value,
+    other: Option<&Value>,
+) -> Result<MergeControl, Error> {
+    match other {
+        Some(other) if !this.is_null() && !other.is_null() => {
+            let (this_kind, other_kind) = (ValueKind::from(&*this), ValueKind::from(other));
+            if this_kind != other_kind {
+                return Err(Error::TypeMismatch {
+                    path: idx.clone(),
+                    this: this_kind,
+                    other: other_kind,
+                });
+            }
+        }
+        _ => {}
+    }
+    Ok(merge_func(idx, this, other).into())
+}
+
+/// Same as [`merge_func`] applied recursively, but moving values out of `other`.
+fn merge_owned_func(this: &mut Value, other: Value) {
+    match (this, other) {
+        (Value::Object(this), Value::Object(other)) => {
+            for (key, other) in other {
+                match this.get_mut(&key) {
+                    Some(this) => merge_owned_func(this, other),
+                    None => {
+                        this.insert(key, other);
+                    }
+                }
+            }
+        }
+        (Value::Array(this), Value::Array(other)) => this.extend(other),
+        (Value::Array(this), other) => this.push(other),
+        (_, Value::Null) => {}
+        (this, other) => *this = other,
+    }
+}
+
+/// The merge function used by [`Merge::merge`] and [`Merge::merge_recursive`].
+///
+/// Objects are merged key by key, other values are appended to arrays
+/// and all remaining values are replaced unless the other value is `null`.
+pub fn merge_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool {
     match (this, other) {
         // add new fields when merging two objects
         (&mut Value::Object(ref mut this), Some(Value::Object(other))) => {
@@ -159,10 +646,13 @@ fn merge_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool
 
 #[cfg(test)]
 pub mod test {
-    use super::Merge;
+    use super::{Cutoff, Error, Merge, MergeControl, ValueKind};
+    use crate::index;
+    use crate::index::{Index, Path as IndexPath};
     use crate::iter::dfs::Dfs;
+    use crate::pointer;
     use pretty_assertions::assert_eq;
-    use serde_json::json;
+    use serde_json::{json, Value};
 
     #[test]
     fn merge_array_string() {
@@ -210,4 +700,422 @@ pub mod test {
         let merge = json!("b");
         assert_eq!(&base.merged_recursive::<Dfs>(&merge), &merge);
     }
+
+    #[test]
+    fn try_merge_strict() {
+        let base = json!({"a": "b", "c": {"d": 1}, "e": null, "f": [1]});
+        let merge = json!({"c": {"d": 2}, "e": {"g": true}, "f": [2], "h": "i"});
+        assert_eq!(base.clone().try_merge_recursive::<Dfs>(&merge), Ok(()));
+        let mut merged = base.clone();
+        merged.try_merge_recursive::<Dfs>(&merge).unwrap();
+        assert_eq!(&merged, &base.merged_recursive::<Dfs>(&merge));
+    }
+
+    #[test]
+    fn try_merge_type_mismatch() {
+        let mut base = json!({"a": "b", "c": {"d": 1, "e": "f"}});
+        let merge = json!({"a": "x", "c": {"d": {"nested": true}}});
+        let err = base.try_merge_recursive::<Dfs>(&merge).unwrap_err();
+        assert_eq!(
+            err,
+            Error::TypeMismatch {
+                path: index!("c", "d"),
+                this: ValueKind::Number,
+                other: ValueKind::Object,
+            }
+        );
+        assert_eq!(err.to_string(), "cannot merge object into number at /c/d");
+
+        // only the first level is checked when not merging recursively
+        let mut base = json!({"a": {"b": 1}});
+        assert_eq!(base.try_merge::<Dfs>(&json!({"a": {"b": "c"}})), Ok(()));
+        assert_eq!(
+            base.try_merge::<Dfs>(&json!({"a": "c"})),
+            Err(Error::TypeMismatch {
+                path: index!("a"),
+                this: ValueKind::Object,
+                other: ValueKind::String,
+            })
+        );
+    }
+
+    #[test]
+    fn try_merge_by_custom_error() {
+        let mut base = json!({"a": {"b": 1}, "c": {"d": 2}});
+        let merge = json!({"a": {"b": 3}, "c": {"d": "forbidden"}});
+        let result = base.try_merge_by_recursive::<Dfs, _, _>(&merge, &mut |idx: &IndexPath,
+                                                                            this: &mut Value,
+                                                                            other: Option<
+            &Value,
+        >| match other {
+            Some(Value::String(s)) if s == "forbidden" => Err(idx.to_string()),
+            Some(Value::Object(_)) => Ok(MergeControl::Descend),
+            Some(other) => {
+                *this = other.clone();
+                Ok(MergeControl::Skip)
+            }
+            None => Ok(MergeControl::Skip),
+        });
+        assert_eq!(result, Err("/c/d".to_string()));
+        assert_eq!(&base, &json!({"a": {"b": 3}, "c": {"d": 2}}));
+    }
+
+    #[test]
+    fn merge_all() {
+        let values = [
+            json!({"a": [1], "b": 1}),
+            json!({"a": [2], "c": {"d": 1}}),
+            json!({"b": 2}),
+        ];
+        assert_eq!(
+            Value::merge_all::<Dfs, _>(&values),
+            json!({"a": [1, 2], "b": 2, "c": {"d": 1}})
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_merge_all_matches_merge_all() {
+        let values: Vec<Value> = (0..1000)
+            .map(|i| {
+                json!({
+                    "id": i % 7,
+                    "list": [i],
+                    "nested": { format!("key{}", i % 11): { "value": i, "null": null } },
+                })
+            })
+            .collect();
+        assert_eq!(
+            Value::par_merge_all::<Dfs, _, _>(&values),
+            Value::merge_all::<Dfs, _>(&values)
+        );
+    }
+
+    /// Sum of merged values, implementing only the required methods.
+    #[derive(Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Merge for Sum {
+        fn merge<T>(&mut self, other: &Self) {
+            self.0 += other.0;
+        }
+
+        fn merge_recursive<T>(&mut self, other: &Self) {
+            self.0 += other.0;
+        }
+
+        fn merge_by<T, F>(&mut self, other: &Self, _merge: &mut F) {
+            self.0 += other.0;
+        }
+
+        fn merge_by_recursive<T, F>(&mut self, other: &Self, _merge: &mut F) {
+            self.0 += other.0;
+        }
+    }
+
+    #[test]
+    fn merge_implementors() {
+        assert_eq!(Sum(1).merged::<Dfs>(&Sum(2)), Sum(3));
+        assert_eq!(Sum(1).merged_recursive::<Dfs>(&Sum(2)), Sum(3));
+    }
+
+    #[test]
+    fn merge_is_not_associative() {
+        let merge = |this: &Value, other: &Value| {
+            let mut this = this.clone();
+            this.merge_recursive::<Dfs>(other);
+            this
+        };
+        let cases = [
+            (
+                [json!([1]), json!("x"), json!("y")],
+                json!([1, "x", "y"]),
+                json!([1, "y"]),
+            ),
+            (
+                [
+                    json!({"x": {"a": 1}}),
+                    json!({"x": 5}),
+                    json!({"x": {"b": 2}}),
+                ],
+                json!({"x": {"b": 2}}),
+                json!({"x": {"a": 1, "b": 2}}),
+            ),
+        ];
+        for ([a, b, c], sequential, tree) in cases {
+            assert_eq!(merge(&merge(&a, &b), &c), sequential);
+            assert_eq!(merge(&a, &merge(&b, &c)), tree);
+
+            let values = [json!({ "v": a }), json!({ "v": b }), json!({ "v": c })];
+            assert_eq!(
+                Value::merge_all::<Dfs, _>(&values),
+                json!({ "v": sequential })
+            );
+            #[cfg(feature = "rayon")]
+            {
+                let merged = Value::par_merge_all::<Dfs, _, _>(&values);
+                assert!(merged["v"] == sequential || merged["v"] == tree);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_to_depth() {
+        let base = json!({
+            "server": { "tls": { "cert": "a.pem", "key": "a.key" }, "port": 80 },
+            "features": { "beta": null },
+            "list": [1],
+        });
+        let merge = json!({
+            "server": { "tls": { "cert": "b.pem" }, "host": "example.com" },
+            "features": { "beta": { "enabled": true }, "alpha": null },
+            "list": [2],
+        });
+        let merged = |depth, cutoff| base.clone().merged_to_depth::<Dfs>(&merge, depth, cutoff);
+        assert_eq!(
+            merged(2, Cutoff::Replace),
+            json!({
+                "server": { "tls": { "cert": "b.pem" }, "port": 80, "host": "example.com" },
+                "features": { "beta": { "enabled": true }, "alpha": null },
+                "list": [1, 2],
+            })
+        );
+        assert_eq!(
+            merged(2, Cutoff::KeepBase),
+            json!({
+                "server": { "tls": { "cert": "a.pem", "key": "a.key" }, "port": 80, "host": "example.com" },
+                "features": { "beta": { "enabled": true }, "alpha": null },
+                "list": [1, 2],
+            })
+        );
+        assert_eq!(
+            merged(1, Cutoff::CloneOther),
+            json!({
+                "server": { "tls": { "cert": "b.pem" }, "host": "example.com" },
+                "features": { "beta": { "enabled": true }, "alpha": null },
+                "list": [2],
+            })
+        );
+        assert_eq!(merged(0, Cutoff::KeepBase), base);
+        assert_eq!(
+            merged(10, Cutoff::Replace),
+            base.clone().merged_recursive::<Dfs>(&merge)
+        );
+    }
+
+    #[test]
+    fn merge_owned() {
+        let base = json!({
+            "a": [1, 2],
+            "b": { "c": "d", "e": [1] },
+            "f": "g",
+            "h": 1,
+        });
+        let merge = json!({
+            "a": [3],
+            "b": { "c": "x", "e": 2, "i": { "j": null } },
+            "f": null,
+            "h": { "k": 1 },
+            "l": null,
+        });
+        assert_eq!(
+            base.clone().merged_owned(merge.clone()),
+            base.merged_recursive::<Dfs>(&merge)
+        );
+    }
+
+    #[test]
+    fn merge_by_control() {
+        let base = json!({ "a": { "b": 1 }, "c": { "d": 1 }, "e": 1 });
+        let merge = json!({ "a": { "b": 2, "x": 2 }, "c": { "d": 2 }, "e": 2 });
+        let merged = |control: fn(&IndexPath) -> Option<MergeControl>| {
+            let mut merged = base.clone();
+            merged.merge_by_recursive_control::<Dfs, _>(&merge, &mut |idx, this, other| {
+                if let Some(control) = control(idx) {
+                    return control;
+                }
+                match (this, other) {
+                    (Value::Object(this), Some(Value::Object(other))) => {
+                        for key in other.keys() {
+                            this.entry(key.clone()).or_insert(Value::Null);
+                        }
+                        MergeControl::Descend
+                    }
+                    (this, Some(other)) => {
+                        this.clone_from(other);
+                        MergeControl::Skip
+                    }
+                    _ => MergeControl::Skip,
+                }
+            });
+            merged
+        };
+        assert_eq!(
+            merged(|idx| (idx == &index!("a", "b")).then_some(MergeControl::SkipSiblings)),
+            json!({ "a": { "b": 1, "x": null }, "c": { "d": 2 }, "e": 2 })
+        );
+        assert_eq!(
+            merged(|idx| (idx == &index!("c")).then_some(MergeControl::Stop)),
+            json!({ "a": { "b": 2, "x": 2 }, "c": { "d": 1 }, "e": 1 })
+        );
+
+        let mut merged = base.clone();
+        merged.merge_by_control::<Dfs, _>(&merge, &mut |_, this, other| {
+            if let Some(other) = other.filter(|_| !this.is_object()) {
+                this.clone_from(other);
+            }
+            MergeControl::Descend
+        });
+        assert_eq!(merged, json!({ "a": { "b": 1 }, "c": { "d": 1 }, "e": 2 }));
+    }
+
+    #[test]
+    fn merge_index() {
+        let mut value = json!({ "a": { "b": [1, { "c": 1 }] }, "d": null, "e": "f" });
+        value
+            .merge_index::<Dfs>(&index!("a", "b", 1), &json!({ "c": 2, "x": 3 }))
+            .unwrap();
+        value
+            .merge_index::<Dfs>(&index!("d", "new", 0), &json!({ "y": 4 }))
+            .unwrap();
+        value
+            .merge_index::<Dfs>(&index!("d", "new", 1), &json!(5))
+            .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "a": { "b": [1, { "c": 2, "x": 3 }] },
+                "d": { "new": [{ "y": 4 }, 5] },
+                "e": "f",
+            })
+        );
+        for arr_idx in [3, 5, usize::MAX] {
+            assert_eq!(
+                value.merge_index::<Dfs>(&index!("d", "new", arr_idx), &json!(1)),
+                Err(Error::IndexOutOfRange {
+                    path: index!("d", "new"),
+                    index: arr_idx,
+                    len: 2,
+                })
+            );
+        }
+        assert_eq!(
+            value.merge_index::<Dfs>(&index!("e", "g"), &json!(1)),
+            Err(Error::TypeMismatch {
+                path: index!("e"),
+                this: ValueKind::String,
+                other: ValueKind::Object,
+            })
+        );
+        assert_eq!(
+            value.merge_index::<Dfs>(&index!("a", 0), &json!(1)),
+            Err(Error::TypeMismatch {
+                path: index!("a"),
+                this: ValueKind::Object,
+                other: ValueKind::Array,
+            })
+        );
+    }
+
+    #[test]
+    fn merge_index_pointer() {
+        let mut value = json!({ "list": [1, { "a": 1 }], "map": { "0": {} } });
+        let resolve = |value: &Value, pointer: &str| {
+            pointer::resolve(value, &IndexPath::from_pointer(pointer).unwrap())
+        };
+        let path = resolve(&value, "/list/1");
+        assert_eq!(path, index!("list", 1));
+        value.merge_index::<Dfs>(&path, &json!({ "b": 2 })).unwrap();
+        assert_eq!(value.get_index(&path), Some(&json!({ "a": 1, "b": 2 })));
+        let path = resolve(&value, "/list/-");
+        value.merge_index::<Dfs>(&path, &json!(3)).unwrap();
+        let path = resolve(&value, "/map/0");
+        assert_eq!(path, index!("map", "0"));
+        value.merge_index::<Dfs>(&path, &json!({ "c": 3 })).unwrap();
+        assert_eq!(
+            value,
+            json!({ "list": [1, { "a": 1, "b": 2 }, 3], "map": { "0": { "c": 3 } } })
+        );
+        // unresolved tokens are object keys
+        assert_eq!(
+            value.merge_index::<Dfs>(&IndexPath::from_pointer("/list/0").unwrap(), &json!(1)),
+            Err(Error::TypeMismatch {
+                path: index!("list"),
+                this: ValueKind::Array,
+                other: ValueKind::Object,
+            })
+        );
+    }
+
+    #[test]
+    fn merge_at() {
+        let mut value = json!({ "list": [{ "name": "a" }], "map": { "0": {} } });
+        value
+            .merge_at::<Dfs>("/list/0", &json!({ "enabled": true }))
+            .unwrap();
+        value.merge_at::<Dfs>("map/0/x", &json!([1])).unwrap();
+        value.merge_at::<Dfs>("/new/1", &json!("value")).unwrap();
+        value.merge_at::<Dfs>("", &json!({ "root": true })).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "list": [{ "name": "a", "enabled": true }],
+                "map": { "0": { "x": [1] } },
+                "new": { "1": "value" },
+                "root": true,
+            })
+        );
+
+        let mut paths = Vec::new();
+        value
+            .merge_at_by::<Dfs, _>("/list/0", &json!({ "name": "b" }), &mut |idx, _, _| {
+                paths.push(idx.clone());
+                true
+            })
+            .unwrap();
+        assert_eq!(paths, vec![index!("list", 0), index!("list", 0, "name")]);
+    }
+
+    #[test]
+    fn merge_at_pointer() {
+        let mut value = json!({ "a/b": { "x": 1 }, "c~d": [1, 2] });
+        assert_eq!(value.get_path("/a~1b"), Some(&json!({ "x": 1 })));
+        value.merge_at::<Dfs>("/a~1b", &json!({ "y": 2 })).unwrap();
+        value.merge_at::<Dfs>("/c~0d/-", &json!(3)).unwrap();
+        value.merge_at::<Dfs>("/new/~01", &json!(true)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "a/b": { "x": 1, "y": 2 },
+                "c~d": [1, 2, 3],
+                "new": { "~1": true },
+            })
+        );
+
+        let mut paths = Vec::new();
+        value
+            .merge_at_by::<Dfs, _>("/c~0d/-", &json!(4), &mut |idx, _, _| {
+                paths.push(idx.clone());
+                true
+            })
+            .unwrap();
+        assert_eq!(paths, vec![index!("c~d", 3)]);
+        assert!(matches!(
+            value.merge_at::<Dfs>("/a~2", &json!(1)),
+            Err(Error::InvalidPointer(_))
+        ));
+        let before = value.clone();
+        for pointer in ["/c~0d/5", "/c~0d/18446744073709551615", "/c~0d/4294967295"] {
+            assert_eq!(
+                value.merge_at::<Dfs>(pointer, &json!(1)),
+                Err(Error::IndexOutOfRange {
+                    path: index!("c~d"),
+                    index: pointer[6..].parse().unwrap(),
+                    len: 4,
+                }),
+                "{pointer}"
+            );
+        }
+        assert_eq!(value, before);
+    }
 }
@@ -0,0 +1,673 @@
+use super::pattern::PathPattern;
+use super::rules::Rule;
+use super::tombstone::Tombstone;
+use super::{Merge, MergeControl};
+use crate::index::Path as IndexPath;
+use crate::iter::Traverser;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub type KeyFunc = Arc<dyn Fn(&Value) -> Option<Value> + Send + Sync>;
+
+/// Identity of array elements used by [`ArrayStrategy::MergeByKey`].
+#[derive(Clone)]
+pub enum ElementKey {
+    /// Elements are identified by the values of the given object fields.
+    Fields(Vec<String>),
+    /// Elements are identified by a custom key.
+    Func(KeyFunc),
+}
+
+impl ElementKey {
+    #[inline]
+    #[must_use]
+    pub fn field(field: impl Into<String>) -> Self {
+        Self::Fields(vec![field.into()])
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn fields<F>(fields: impl IntoIterator<Item = F>) -> Self
+    where
+        F: Into<String>,
+    {
+        Self::Fields(fields.into_iter().map(Into::into).collect())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn func(func: impl Fn(&Value) -> Option<Value> + Send + Sync + 'static) -> Self {
+        Self::Func(Arc::new(func))
+    }
+
+    /// Returns the key of `value`.
+    ///
+    /// Elements without a key, such as objects missing one of the key fields,
+    /// are never matched.
+    #[inline]
+    #[must_use]
+    pub fn key(&self, value: &Value) -> Option<Value> {
+        match self {
+            Self::Fields(fields) => {
+                let values = fields
+                    .iter()
+                    .map(|field| value.get(field).cloned())
+                    .collect::<Option<Vec<Value>>>()?;
+                Some(Value::Array(values))
+            }
+            Self::Func(func) => func(value),
+        }
+    }
+}
+
+impl std::fmt::Debug for ElementKey {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fields(fields) => f.debug_tuple("Fields").field(fields).finish(),
+            Self::Func(_) => f.debug_tuple("Func").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Strategy for merging two arrays.
+#[derive(Debug, Clone, Default)]
+pub enum ArrayStrategy {
+    /// Append the elements of the other array.
+    #[default]
+    Append,
+    /// Prepend the elements of the other array.
+    Prepend,
+    /// Replace the array with the other array.
+    Replace,
+    /// Merge elements with the same index and append the remaining elements.
+    MergeByIndex,
+    /// Append elements of the other array that are not yet contained.
+    AppendUnique,
+    /// Merge elements with the same key and append the remaining elements.
+    ///
+    /// Matched elements are merged recursively using the same options,
+    /// and the order of the base array is preserved.
+    MergeByKey(ElementKey),
+}
+
+/// Policy for `null` values of the other value.
+///
+/// Overlays created using [`serde_json::to_value`] contain `null` for `None` fields.
+/// Use [`NullPolicy::Ignore`] to leave the base untouched for those fields, or skip
+/// serializing `None` fields using `#[serde(skip_serializing_if = "Option::is_none")]`
+/// and use [`NullPolicy::Clear`] so that the remaining (e.g. `Some(None)`) `null`
+/// values clear the base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NullPolicy {
+    /// Never overwrite values with `null`, but add missing object keys as `null`.
+    #[default]
+    Merge,
+    /// Ignore `null` values and do not add missing object keys that are `null`.
+    ///
+    /// Elements of arrays merged by a strategy other than
+    /// [`ArrayStrategy::MergeByIndex`] are not affected.
+    Ignore,
+    /// Remove object keys that are `null` in the other value.
+    ///
+    /// Other `null` values, such as array elements, are ignored.
+    Clear,
+    /// Overwrite values with `null`.
+    Overwrite,
+}
+
+/// Options for [`Merge::merge_with`](super::Merge::merge_with).
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    array_strategy: ArrayStrategy,
+    array_strategies: Vec<(PathPattern, ArrayStrategy)>,
+    rules: Vec<(PathPattern, Rule)>,
+    tombstone: Option<Tombstone>,
+    null_policy: NullPolicy,
+    null_policies: Vec<(PathPattern, NullPolicy)>,
+}
+
+impl MergeOptions {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the default strategy for merging arrays.
+    #[inline]
+    #[must_use]
+    pub fn array_strategy(mut self, strategy: ArrayStrategy) -> Self {
+        self.array_strategy = strategy;
+        self
+    }
+
+    /// Sets the strategy for merging arrays at paths matching `pattern`.
+    ///
+    /// If multiple patterns match, the one added first is used.
+    #[inline]
+    #[must_use]
+    pub fn array_strategy_at(
+        mut self,
+        pattern: impl Into<PathPattern>,
+        strategy: ArrayStrategy,
+    ) -> Self {
+        self.array_strategies.push((pattern.into(), strategy));
+        self
+    }
+
+    /// Returns the strategy for merging arrays at `idx`.
+    #[inline]
+    #[must_use]
+    pub fn array_strategy_for(&self, idx: &IndexPath) -> &ArrayStrategy {
+        self.array_strategies
+            .iter()
+            .find(|(pattern, _)| pattern.matches(idx))
+            .map_or(&self.array_strategy, |(_, strategy)| strategy)
+    }
+
+    /// Adds a rule for merging values at paths matching `pattern`.
+    ///
+    /// If multiple patterns match, the most specific one
+    /// (see [`PathPattern::specificity`]) is used, or the one added first if they
+    /// are equally specific. Values not matched by any rule are merged as usual.
+    #[inline]
+    #[must_use]
+    pub fn rule(mut self, pattern: impl Into<PathPattern>, rule: Rule) -> Self {
+        self.rules.push((pattern.into(), rule));
+        self
+    }
+
+    /// Sets the default policy for `null` values of the other value.
+    #[inline]
+    #[must_use]
+    pub fn null_policy(mut self, policy: NullPolicy) -> Self {
+        self.null_policy = policy;
+        self
+    }
+
+    /// Sets the policy for `null` values of the other value at paths matching `pattern`.
+    ///
+    /// If multiple patterns match, the one added first is used.
+    #[inline]
+    #[must_use]
+    pub fn null_policy_at(mut self, pattern: impl Into<PathPattern>, policy: NullPolicy) -> Self {
+        self.null_policies.push((pattern.into(), policy));
+        self
+    }
+
+    /// Returns the policy for `null` values at `idx`.
+    #[inline]
+    #[must_use]
+    pub fn null_policy_for(&self, idx: &IndexPath) -> NullPolicy {
+        self.null_policies
+            .iter()
+            .find(|(pattern, _)| pattern.matches(idx))
+            .map_or(self.null_policy, |(_, policy)| *policy)
+    }
+
+    /// Deletes values marked with `tombstone` instead of merging them.
+    #[inline]
+    #[must_use]
+    pub fn tombstone(mut self, tombstone: Tombstone) -> Self {
+        self.tombstone = Some(tombstone);
+        self
+    }
+
+    /// Returns the rule for merging values at `idx`.
+    #[inline]
+    #[must_use]
+    pub fn rule_for(&self, idx: &IndexPath) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .rev()
+            .filter(|(pattern, _)| pattern.matches(idx))
+            .max_by_key(|(pattern, _)| pattern.specificity())
+            .map(|(_, rule)| rule)
+    }
+
+    pub(crate) fn merge_func<T>(
+        &self,
+        idx: &IndexPath,
+        this: &mut Value,
+        other: Option<&Value>,
+    ) -> MergeControl
+    where
+        T: Traverser,
+    {
+        match &self.tombstone {
+            Some(tombstone) => tombstone.merge(idx, this, other, &mut |idx, this, other| {
+                self.merge_rules_func::<T>(idx, this, other)
+            }),
+            None => self.merge_rules_func::<T>(idx, this, other),
+        }
+    }
+
+    fn merge_rules_func<T>(
+        &self,
+        idx: &IndexPath,
+        this: &mut Value,
+        other: Option<&Value>,
+    ) -> MergeControl
+    where
+        T: Traverser,
+    {
+        let rule = self.rule_for(idx);
+        match (rule, this, other) {
+            (Some(Rule::Skip), _, _) | (_, _, None) => MergeControl::Skip,
+            (Some(Rule::Overwrite), this, Some(other)) => {
+                this.clone_from(other);
+                MergeControl::Skip
+            }
+            (Some(Rule::KeepBase), this, Some(other)) => {
+                // missing values are added as null placeholders
+                if this.is_null() {
+                    this.clone_from(other);
+                }
+                MergeControl::Skip
+            }
+            (Some(Rule::Custom(func)), this, other) => func(idx, this, other),
+            (rule, Value::Array(ref mut this), Some(Value::Array(other))) => {
+                let strategy = match rule {
+                    Some(Rule::Array(strategy)) => strategy,
+                    _ => self.array_strategy_for(idx),
+                };
+                self.merge_arrays::<T>(idx, strategy, this, other).into()
+            }
+            (_, Value::Object(ref mut this), Some(Value::Object(other))) => {
+                for (key, value) in other {
+                    let mut key_idx = idx.clone();
+                    key_idx.add(key.clone());
+                    if matches!(self.rule_for(&key_idx), Some(Rule::Skip)) {
+                        continue;
+                    }
+                    match (value, self.null_policy_for(&key_idx)) {
+                        (Value::Null, NullPolicy::Ignore) => {}
+                        (Value::Null, NullPolicy::Clear) => {
+                            this.shift_remove(key);
+                        }
+                        (Value::Null, NullPolicy::Overwrite) => {
+                            this.insert(key.clone(), Value::Null);
+                        }
+                        _ => {
+                            this.entry(key.clone()).or_insert(Value::Null);
+                        }
+                    }
+                }
+                MergeControl::Descend
+            }
+            (_, this, Some(Value::Null)) if self.null_policy_for(idx) == NullPolicy::Overwrite => {
+                *this = Value::Null;
+                MergeControl::Skip
+            }
+            (_, this, other) => super::merge_func(idx, this, other).into(),
+        }
+    }
+
+    fn merge_arrays<T>(
+        &self,
+        idx: &IndexPath,
+        strategy: &ArrayStrategy,
+        this: &mut Vec<Value>,
+        other: &[Value],
+    ) -> bool
+    where
+        T: Traverser,
+    {
+        match strategy {
+            ArrayStrategy::Append => {
+                this.extend_from_slice(other);
+                false
+            }
+            ArrayStrategy::Prepend => {
+                this.splice(0..0, other.iter().cloned());
+                false
+            }
+            ArrayStrategy::Replace => {
+                *this = other.to_vec();
+                false
+            }
+            ArrayStrategy::MergeByIndex => {
+                // add placeholders that are filled in when merging the elements
+                if other.len() > this.len() {
+                    this.resize(other.len(), Value::Null);
+                }
+                true
+            }
+            ArrayStrategy::AppendUnique => {
+                for value in other {
+                    if !this.contains(value) {
+                        this.push(value.clone());
+                    }
+                }
+                false
+            }
+            ArrayStrategy::MergeByKey(key) => {
+                self.merge_arrays_by_key::<T>(idx, key, this, other);
+                false
+            }
+        }
+    }
+
+    fn merge_arrays_by_key<T>(
+        &self,
+        idx: &IndexPath,
+        key: &ElementKey,
+        this: &mut Vec<Value>,
+        other: &[Value],
+    ) where
+        T: Traverser,
+    {
+        for value in other {
+            let matched = key.key(value).and_then(|value_key| {
+                this.iter()
+                    .position(|existing| key.key(existing).as_ref() == Some(&value_key))
+            });
+            let Some(arr_idx) = matched else {
+                this.push(value.clone());
+                continue;
+            };
+            // merge matched elements using the paths of the base array
+            let mut element_idx = idx.clone();
+            element_idx.add(arr_idx);
+            this[arr_idx].merge_by_recursive_control::<T, _>(value, &mut |sub_idx, this, other| {
+                self.merge_func::<T>(&element_idx.clone().join(sub_idx), this, other)
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{ArrayStrategy, ElementKey, MergeOptions, NullPolicy};
+    use crate::iter::dfs::Dfs;
+    use crate::merge::Rule;
+    use crate::merge::{Merge, MergeControl};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn merge_with_default_options() {
+        let base = json!({"a": [1, 2], "b": {"c": "d"}});
+        let merge = json!({"a": [2, 3], "b": {"c": "e", "f": null}});
+        assert_eq!(
+            &base
+                .clone()
+                .merged_with::<Dfs>(&merge, &MergeOptions::default()),
+            &base.merged_recursive::<Dfs>(&merge),
+        );
+    }
+
+    #[test]
+    fn merge_with_array_strategies() {
+        let base = json!([1, 2, {"a": 1, "b": [1]}]);
+        let merge = json!([2, 3, {"a": 2, "b": [2]}, 4]);
+
+        let merged = |strategy| {
+            base.clone()
+                .merged_with::<Dfs>(&merge, &MergeOptions::new().array_strategy(strategy))
+        };
+        assert_eq!(
+            merged(ArrayStrategy::Append),
+            json!([1, 2, {"a": 1, "b": [1]}, 2, 3, {"a": 2, "b": [2]}, 4])
+        );
+        assert_eq!(
+            merged(ArrayStrategy::Prepend),
+            json!([2, 3, {"a": 2, "b": [2]}, 4, 1, 2, {"a": 1, "b": [1]}])
+        );
+        assert_eq!(merged(ArrayStrategy::Replace), merge);
+        assert_eq!(
+            merged(ArrayStrategy::MergeByIndex),
+            json!([2, 3, {"a": 2, "b": [2]}, 4])
+        );
+        assert_eq!(
+            merged(ArrayStrategy::AppendUnique),
+            json!([1, 2, {"a": 1, "b": [1]}, 3, {"a": 2, "b": [2]}, 4])
+        );
+    }
+
+    #[test]
+    fn merge_by_index_recursive() {
+        let base = json!({"list": [{"a": 1, "b": [1]}, {"c": 1}]});
+        let merge = json!({"list": [{"a": 2, "b": [2]}, null, {"d": 1}]});
+        let options = MergeOptions::new().array_strategy(ArrayStrategy::MergeByIndex);
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({"list": [{"a": 2, "b": [2]}, {"c": 1}, {"d": 1}]})
+        );
+    }
+
+    #[test]
+    fn merge_with_array_strategy_per_path() {
+        let base = json!({
+            "hosts": ["a", "b"],
+            "plugins": ["x", "y"],
+            "services": {
+                "web": { "ports": [80], "env": ["A=1"] },
+                "db": { "ports": [5432], "env": ["B=1"] },
+            },
+        });
+        let merge = json!({
+            "hosts": ["c"],
+            "plugins": ["y", "z"],
+            "services": {
+                "web": { "ports": [443], "env": ["A=2"] },
+                "db": { "ports": [5432], "env": ["B=2"] },
+            },
+        });
+        let options = MergeOptions::new()
+            .array_strategy_at("/hosts", ArrayStrategy::Replace)
+            .array_strategy_at("/plugins", ArrayStrategy::AppendUnique)
+            .array_strategy_at("/services/web/ports", ArrayStrategy::Prepend)
+            .array_strategy_at("/services/*/ports", ArrayStrategy::AppendUnique);
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({
+                "hosts": ["c"],
+                "plugins": ["x", "y", "z"],
+                "services": {
+                    "web": { "ports": [443, 80], "env": ["A=1", "A=2"] },
+                    "db": { "ports": [5432], "env": ["B=1", "B=2"] },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn merge_by_key_fields() {
+        let base = json!({
+            "containers": [
+                { "name": "app", "image": "app:1", "ports": [80], "env": { "A": "1" } },
+                { "name": "sidecar", "image": "proxy:1" },
+            ],
+        });
+        let merge = json!({
+            "containers": [
+                { "name": "log", "image": "log:1" },
+                { "name": "app", "image": "app:2", "ports": [443], "env": { "B": "2" } },
+                { "image": "unnamed:1" },
+            ],
+        });
+        let options = MergeOptions::new()
+            .array_strategy_at(
+                "/containers",
+                ArrayStrategy::MergeByKey(ElementKey::field("name")),
+            )
+            .array_strategy_at("/containers/*/ports", ArrayStrategy::Replace);
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({
+                "containers": [
+                    {
+                        "name": "app",
+                        "image": "app:2",
+                        "ports": [443],
+                        "env": { "A": "1", "B": "2" },
+                    },
+                    { "name": "sidecar", "image": "proxy:1" },
+                    { "name": "log", "image": "log:1" },
+                    { "image": "unnamed:1" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn merge_by_multiple_key_fields() {
+        let base = json!([
+            { "port": 80, "protocol": "TCP", "name": "http" },
+            { "port": 80, "protocol": "UDP" },
+        ]);
+        let merge = json!([
+            { "port": 80, "protocol": "UDP", "name": "quic" },
+            { "port": 443, "protocol": "TCP" },
+        ]);
+        let options =
+            MergeOptions::new().array_strategy(ArrayStrategy::MergeByKey(ElementKey::fields([
+                "port", "protocol",
+            ])));
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!([
+                { "port": 80, "protocol": "TCP", "name": "http" },
+                { "port": 80, "protocol": "UDP", "name": "quic" },
+                { "port": 443, "protocol": "TCP" },
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_by_key_func() {
+        let base = json!(["a=1", "b=2", { "nested": [{ "id": 1, "value": "x" }] }]);
+        let merge = json!(["b=3", "c=4", { "nested": [{ "id": 1, "other": "y" }] }]);
+        let key = ElementKey::func(|value| match value {
+            serde_json::Value::String(s) => s.split('=').next().map(Into::into),
+            serde_json::Value::Object(_) => Some(json!("object")),
+            _ => None,
+        });
+        let options = MergeOptions::new()
+            .array_strategy(ArrayStrategy::MergeByKey(ElementKey::field("id")))
+            .array_strategy_at("/", ArrayStrategy::MergeByKey(key));
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!([
+                "a=1",
+                "b=3",
+                { "nested": [{ "id": 1, "value": "x", "other": "y" }] },
+                "c=4",
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_with_rules() {
+        let base = json!({
+            "version": 3,
+            "services": {
+                "web": { "image": "web:1", "env": { "A": "1" }, "ports": [80] },
+                "db": { "image": "db:1", "env": { "B": "1" }, "ports": [5432] },
+            },
+            "plugins": [{ "name": "a" }],
+            "secrets": { "token": "base" },
+        });
+        let merge = json!({
+            "version": 2,
+            "services": {
+                "web": { "image": "web:2", "env": { "C": "2" }, "ports": [443] },
+                "db": { "image": "db:2", "env": { "B": "2" }, "ports": [5432] },
+                "cache": { "image": "cache:1" },
+            },
+            "plugins": [{ "name": "b" }],
+            "secrets": { "token": "other", "key": "other" },
+            "internal": { "debug": true },
+        });
+        let options = MergeOptions::new()
+            .rule(
+                "/version",
+                Rule::custom(|_, this, other| {
+                    // keep the highest version
+                    if let Some(other) = other.filter(|other| other.as_u64() > this.as_u64()) {
+                        this.clone_from(other);
+                    }
+                    false
+                }),
+            )
+            .rule("/services/*/env/**", Rule::Overwrite)
+            .rule("/services/*/env", Rule::Overwrite)
+            .rule("/services/db/env", Rule::KeepBase)
+            .rule("/services/*/image", Rule::KeepBase)
+            .rule(
+                "/services/**/ports",
+                Rule::Array(ArrayStrategy::AppendUnique),
+            )
+            .rule("/plugins/[*]", Rule::Skip)
+            .rule("/plugins", Rule::Array(ArrayStrategy::Replace))
+            .rule("/secrets/*", Rule::Skip)
+            .rule("/internal", Rule::Skip);
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({
+                "version": 3,
+                "services": {
+                    "web": { "image": "web:1", "env": { "C": "2" }, "ports": [80, 443] },
+                    "db": { "image": "db:1", "env": { "B": "1" }, "ports": [5432] },
+                    "cache": { "image": "cache:1" },
+                },
+                "plugins": [{ "name": "b" }],
+                "secrets": { "token": "base" },
+            })
+        );
+    }
+
+    #[test]
+    fn merge_with_custom_rule_control() {
+        let base = json!({ "a": { "x": 1, "y": 1 }, "b": 1 });
+        let merge = json!({ "a": { "x": 2, "y": 2 }, "b": 2 });
+        let options =
+            MergeOptions::new().rule("/a/x", Rule::custom(|_, _, _| MergeControl::SkipSiblings));
+        assert_eq!(
+            base.clone().merged_with::<Dfs>(&merge, &options),
+            json!({ "a": { "x": 1, "y": 1 }, "b": 2 })
+        );
+        let options = MergeOptions::new().rule("/a", Rule::custom(|_, _, _| MergeControl::Stop));
+        assert_eq!(base.clone().merged_with::<Dfs>(&merge, &options), base);
+    }
+
+    #[test]
+    fn merge_with_null_policies() {
+        let base = json!({ "a": 1, "b": { "c": 2 }, "list": [1, 2] });
+        let merge = json!({ "a": null, "b": { "c": null, "d": null }, "list": [null], "e": null });
+        let merged = |policy| {
+            base.clone().merged_with::<Dfs>(
+                &merge,
+                &MergeOptions::new()
+                    .null_policy(policy)
+                    .array_strategy(ArrayStrategy::MergeByIndex),
+            )
+        };
+        assert_eq!(
+            merged(NullPolicy::Merge),
+            json!({ "a": 1, "b": { "c": 2, "d": null }, "list": [1, 2], "e": null })
+        );
+        assert_eq!(
+            merged(NullPolicy::Ignore),
+            json!({ "a": 1, "b": { "c": 2 }, "list": [1, 2] })
+        );
+        assert_eq!(
+            merged(NullPolicy::Clear),
+            json!({ "b": {}, "list": [1, 2] })
+        );
+        assert_eq!(
+            merged(NullPolicy::Overwrite),
+            json!({ "a": null, "b": { "c": null, "d": null }, "list": [null, 2], "e": null })
+        );
+
+        let options = MergeOptions::new()
+            .null_policy(NullPolicy::Ignore)
+            .null_policy_at("/b/**", NullPolicy::Clear);
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({ "a": 1, "b": {}, "list": [1, 2, null] })
+        );
+    }
+}
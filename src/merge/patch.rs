@@ -0,0 +1,211 @@
+use crate::index::{Index, Path as IndexPath};
+use crate::iter::Traverser;
+use serde_json::{Map, Value};
+
+/// Computes the JSON Merge Patch that turns `source` into `target`.
+///
+/// Applying the returned patch to `source` using [`Merge::merge_patch`]
+/// yields `target`.
+/// Removed keys are expressed as `null` and arrays are replaced as a whole.
+///
+/// Note that [RFC 7396](https://datatracker.ietf.org/doc/html/rfc7396)
+/// cannot express object members whose value is explicitly `null`,
+/// hence such members of `target` will not round-trip.
+///
+/// [`Merge::merge_patch`]: super::Merge::merge_patch
+#[must_use]
+pub fn create_merge_patch<T>(source: &Value, target: &Value) -> Value
+where
+    T: Traverser,
+{
+    if !(source.is_object() && target.is_object()) {
+        return target.clone();
+    }
+    let mut patch = Value::Object(Map::default());
+    let mut traverser = T::new();
+    traverser.set_limit(None);
+    traverser.set_depth(None);
+    while traverser
+        .process_next(target, |idx, new_value| {
+            let Some(new_value) = new_value else {
+                return false;
+            };
+            match (source.get_index(idx), new_value) {
+                (Some(old_value), new_value) if old_value == new_value => false,
+                (Some(Value::Object(old)), Value::Object(new)) => {
+                    for k in old.keys().filter(|k| !new.contains_key(*k)) {
+                        patch[idx][k] = Value::Null;
+                    }
+                    true
+                }
+                (_, new_value) => {
+                    patch[idx] = new_value.clone();
+                    false
+                }
+            }
+        })
+        .is_some()
+    {}
+    patch
+}
+
+/// Merge function implementing [RFC 7396](https://datatracker.ietf.org/doc/html/rfc7396).
+///
+/// - `null` values in the patch remove the key from the target
+/// - objects are merged recursively
+/// - any other patch value (including arrays) replaces the target
+pub(crate) fn merge_patch_func(_idx: &IndexPath, this: &mut Value, patch: Option<&Value>) -> bool {
+    match (this, patch) {
+        (this, Some(Value::Object(patch))) => {
+            if !this.is_object() {
+                *this = Value::Object(Map::default());
+            }
+            if let Value::Object(ref mut this) = this {
+                for (k, v) in patch {
+                    if v.is_null() {
+                        this.shift_remove(k);
+                    } else {
+                        this.entry(k.clone()).or_insert(Value::Null);
+                    }
+                }
+            }
+            true
+        }
+        (this, Some(patch)) => {
+            *this = patch.clone();
+            false
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::create_merge_patch;
+    use crate::iter::dfs::Dfs;
+    use crate::merge::Merge;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    macro_rules! merge_patch_tests {
+        ($($name:ident: $target:tt, $patch:tt => $expected:tt,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let target = json!($target);
+                    let patch = json!($patch);
+                    assert_eq!(&target.merged_patch::<Dfs>(&patch), &json!($expected));
+                }
+            )*
+        }
+    }
+
+    // test vectors from RFC 7396 appendix A
+    merge_patch_tests!(
+        rfc7396_replace_value: {"a": "b"}, {"a": "c"} => {"a": "c"},
+        rfc7396_add_value: {"a": "b"}, {"b": "c"} => {"a": "b", "b": "c"},
+        rfc7396_remove_value: {"a": "b"}, {"a": null} => {},
+        rfc7396_remove_one_of_many: {"a": "b", "b": "c"}, {"a": null} => {"b": "c"},
+        rfc7396_replace_array_with_string: {"a": ["b"]}, {"a": "c"} => {"a": "c"},
+        rfc7396_replace_string_with_array: {"a": "c"}, {"a": ["b"]} => {"a": ["b"]},
+        rfc7396_nested: {"a": {"b": "c"}}, {"a": {"b": "d", "c": null}} => {"a": {"b": "d"}},
+        rfc7396_replace_array_of_objects: {"a": [{"b": "c"}]}, {"a": [1]} => {"a": [1]},
+        rfc7396_replace_array: ["a", "b"], ["c", "d"] => ["c", "d"],
+        rfc7396_replace_object_with_array: {"a": "b"}, ["c"] => ["c"],
+        rfc7396_replace_with_null: {"a": "foo"}, null => null,
+        rfc7396_replace_with_string: {"a": "foo"}, "bar" => "bar",
+        rfc7396_keep_null_in_target: {"e": null}, {"a": 1} => {"e": null, "a": 1},
+        rfc7396_replace_array_with_object: [1, 2], {"a": "b", "c": null} => {"a": "b"},
+        rfc7396_create_nested: {}, {"a": {"bb": {"ccc": null}}} => {"a": {"bb": {}}},
+    );
+
+    macro_rules! create_merge_patch_tests {
+        ($($name:ident: $source:tt, $target:tt => $expected:tt,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let source = json!($source);
+                    let target = json!($target);
+                    let patch = create_merge_patch::<Dfs>(&source, &target);
+                    assert_eq!(&patch, &json!($expected));
+                    assert_eq!(&source.merged_patch::<Dfs>(&patch), &target);
+                }
+            )*
+        }
+    }
+
+    create_merge_patch_tests!(
+        create_merge_patch_unchanged: {"a": "b"}, {"a": "b"} => {},
+        create_merge_patch_replace_value: {"a": "b"}, {"a": "c"} => {"a": "c"},
+        create_merge_patch_add_value: {"a": "b"}, {"a": "b", "b": "c"} => {"b": "c"},
+        create_merge_patch_remove_value: {"a": "b", "b": "c"}, {"b": "c"} => {"a": null},
+        create_merge_patch_replace_array: {"a": [1, 2]}, {"a": [1]} => {"a": [1]},
+        create_merge_patch_nested: {"a": {"b": "c", "d": {"e": 1}}}, {"a": {"b": "d", "d": {"e": 1}}}
+            => {"a": {"b": "d"}},
+        create_merge_patch_nested_remove: {"a": {"b": {"c": 1, "d": 2}}}, {"a": {"b": {"d": 2}}}
+            => {"a": {"b": {"c": null}}},
+        create_merge_patch_object_to_string: {"a": {"b": "c"}}, {"a": "b"} => {"a": "b"},
+        create_merge_patch_string_to_object: {"a": "b"}, {"a": {"b": "c"}} => {"a": {"b": "c"}},
+        create_merge_patch_keep_null_in_source: {"a": null, "b": 1}, {"a": null, "b": 2} => {"b": 2},
+        create_merge_patch_root_array: [1, 2], [3] => [3],
+        create_merge_patch_root_scalar: "a", "a" => "a",
+        create_merge_patch_object_to_array: {"a": 1}, [1] => [1],
+    );
+
+    #[test]
+    fn create_merge_patch_complex() {
+        let source = json!({
+            "title": "Goodbye!",
+            "author" : {
+                "givenName" : "John",
+                "familyName" : "Doe"
+            },
+            "tags": [ "example", "sample" ],
+            "content": "This will be unchanged"
+        });
+        let target = json!({
+            "title": "Hello!",
+            "author" : {
+                "givenName" : "John"
+            },
+            "tags": [ "example" ],
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-123-456-7890"
+        });
+        let patch = create_merge_patch::<Dfs>(&source, &target);
+        assert_eq!(
+            &patch,
+            &json!({
+                "title": "Hello!",
+                "phoneNumber": "+01-123-456-7890",
+                "author": {
+                    "familyName": null
+                },
+                "tags": [ "example" ]
+            })
+        );
+        assert_eq!(&source.merged_patch::<Dfs>(&patch), &target);
+    }
+
+    #[test]
+    fn merge_patch_arrays_are_not_extended() {
+        let base = json!({"a": [1, 2], "b": {"c": [3]}});
+        let patch = json!({"a": [3], "b": {"c": []}});
+        assert_eq!(
+            &base.merged_patch::<Dfs>(&patch),
+            &json!({"a": [3], "b": {"c": []}})
+        );
+    }
+
+    #[cfg(all(feature = "sort", feature = "preserve_order"))]
+    #[test]
+    fn merge_patch_preserves_order() {
+        use crate::test::assert_eq_ordered;
+        let base = json!({"a": 1, "b": 2, "c": 3, "d": 4});
+        let patch = json!({"b": null, "e": 5});
+        assert_eq_ordered!(
+            &base.merged_patch::<Dfs>(&patch),
+            &json!({"a": 1, "c": 3, "d": 4, "e": 5})
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::index::{IndexRef, Kind as IndexKind, Path as IndexPath};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// Matches an object key or an array index with the same string representation.
+    Literal(String),
+    /// Matches the array index (`[3]`).
+    Index(usize),
+    /// Matches any single object key or array index (`*`).
+    Any,
+    /// Matches any array index (`[*]`).
+    AnyIndex,
+    /// Matches zero or more segments (`**`).
+    Recursive,
+}
+
+impl Segment {
+    #[inline]
+    fn matches(&self, index: &IndexRef) -> bool {
+        match (self, index.kind()) {
+            (Self::Any | Self::Recursive, _) | (Self::AnyIndex, IndexKind::ArrayIndex(_)) => true,
+            (Self::Literal(literal), IndexKind::ObjectKey(key)) => literal == key,
+            (Self::Literal(literal), IndexKind::ArrayIndex(idx)) => *literal == idx.to_string(),
+            (Self::Index(expected), IndexKind::ArrayIndex(idx)) => expected == idx,
+            (Self::Index(_) | Self::AnyIndex, IndexKind::ObjectKey(_)) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Segment {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(key) => write!(f, "{}", key.replace('~', "~0").replace('/', "~1")),
+            Self::Index(idx) => write!(f, "[{idx}]"),
+            Self::Any => write!(f, "*"),
+            Self::AnyIndex => write!(f, "[*]"),
+            Self::Recursive => write!(f, "**"),
+        }
+    }
+}
+
+/// Glob pattern over index paths.
+///
+/// Segments are separated by `/`:
+/// - `*` matches any single object key or array index
+/// - `[*]` matches any array index and `[3]` matches the array index `3`
+/// - `**` matches zero or more segments
+/// - any other segment matches an object key literally,
+///   where `~1` and `~0` escape `/` and `~` like in JSON pointers.
+///
+/// For example, `/services/*/env/**` matches `/services/web/env`
+/// and `/services/web/env/PORT`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PathPattern(Vec<Segment>);
+
+impl PathPattern {
+    #[inline]
+    #[must_use]
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        if pattern.is_empty() {
+            return Self::default();
+        }
+        let segments = pattern.split('/').map(|segment| match segment {
+            "**" => Segment::Recursive,
+            "*" => Segment::Any,
+            "[*]" => Segment::AnyIndex,
+            segment => segment
+                .strip_prefix('[')
+                .and_then(|segment| segment.strip_suffix(']'))
+                .and_then(|idx| idx.parse().ok())
+                .map_or_else(
+                    || Segment::Literal(segment.replace("~1", "/").replace("~0", "~")),
+                    Segment::Index,
+                ),
+        });
+        Self(segments.collect())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+
+    /// Returns how specific the pattern is, where more specific patterns compare greater.
+    ///
+    /// Patterns with more literal segments are more specific,
+    /// followed by patterns with more segments other than `**`.
+    #[inline]
+    #[must_use]
+    pub fn specificity(&self) -> (usize, usize) {
+        let exact = self
+            .0
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Literal(_) | Segment::Index(_)))
+            .count();
+        let bounded = self
+            .0
+            .iter()
+            .filter(|segment| segment != &&Segment::Recursive)
+            .count();
+        (exact, bounded)
+    }
+
+    /// Checks if the pattern matches the full index path.
+    #[must_use]
+    pub fn matches(&self, path: &IndexPath) -> bool {
+        // iterative glob matching with backtracking to the last `**`
+        let (pattern, path) = (&self.0, &path[..]);
+        let (mut p, mut i) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while i < path.len() {
+            match pattern.get(p) {
+                Some(Segment::Recursive) => {
+                    backtrack = Some((p, i));
+                    p += 1;
+                }
+                Some(segment) if segment.matches(&path[i]) => {
+                    p += 1;
+                    i += 1;
+                }
+                _ => match backtrack {
+                    Some((star_p, star_i)) => {
+                        backtrack = Some((star_p, star_i + 1));
+                        p = star_p + 1;
+                        i = star_i + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p.min(pattern.len())..]
+            .iter()
+            .all(|segment| segment == &Segment::Recursive)
+    }
+}
+
+impl std::fmt::Display for PathPattern {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "/{}",
+            self.0
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("/")
+        )
+    }
+}
+
+impl From<&str> for PathPattern {
+    #[inline]
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+impl From<String> for PathPattern {
+    #[inline]
+    fn from(pattern: String) -> Self {
+        Self::new(&pattern)
+    }
+}
+
+impl From<&IndexPath> for PathPattern {
+    #[inline]
+    fn from(path: &IndexPath) -> Self {
+        Self(
+            path.iter()
+                .map(|index| match index.kind() {
+                    IndexKind::ObjectKey(key) => Segment::Literal(key.to_string()),
+                    IndexKind::ArrayIndex(idx) => Segment::Index(*idx),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl std::str::FromStr for PathPattern {
+    type Err = std::convert::Infallible;
+
+    #[inline]
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(pattern))
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{PathPattern, Segment};
+    use crate::index;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_pattern() {
+        assert_eq!(PathPattern::new("").segments(), &[]);
+        assert_eq!(PathPattern::new("/").segments(), &[]);
+        assert_eq!(
+            PathPattern::new("/services/*/env/**").segments(),
+            &[
+                Segment::Literal("services".into()),
+                Segment::Any,
+                Segment::Literal("env".into()),
+                Segment::Recursive,
+            ]
+        );
+        assert_eq!(
+            PathPattern::new("plugins/[*]/[2]/a~1b").segments(),
+            &[
+                Segment::Literal("plugins".into()),
+                Segment::AnyIndex,
+                Segment::Index(2),
+                Segment::Literal("a/b".into()),
+            ]
+        );
+        assert_eq!(
+            PathPattern::new("/services/*/env/**").to_string(),
+            "/services/*/env/**"
+        );
+        assert_eq!(
+            PathPattern::from(&index!("a/b", 1)).to_string(),
+            "/a~1b/[1]"
+        );
+    }
+
+    #[test]
+    fn pattern_matches() {
+        let pattern = PathPattern::new("/services/*/env/**");
+        assert!(pattern.matches(&index!("services", "web", "env")));
+        assert!(pattern.matches(&index!("services", "web", "env", "PORT")));
+        assert!(pattern.matches(&index!("services", 0, "env", "a", 1)));
+        assert!(!pattern.matches(&index!("services", "web")));
+        assert!(!pattern.matches(&index!("services", "web", "other", "env")));
+
+        let pattern = PathPattern::new("/plugins/[*]");
+        assert!(pattern.matches(&index!("plugins", 0)));
+        assert!(!pattern.matches(&index!("plugins", "0")));
+        assert!(!pattern.matches(&index!("plugins")));
+        assert!(!pattern.matches(&index!("plugins", 0, "name")));
+
+        let pattern = PathPattern::new("/list/1");
+        assert!(pattern.matches(&index!("list", 1)));
+        assert!(pattern.matches(&index!("list", "1")));
+
+        let pattern = PathPattern::new("/**/name");
+        assert!(pattern.matches(&index!("name")));
+        assert!(pattern.matches(&index!("a", 0, "name")));
+        assert!(!pattern.matches(&index!("a", "name", "b")));
+
+        let pattern = PathPattern::new("/**");
+        assert!(pattern.matches(&index!()));
+        assert!(pattern.matches(&index!("a", "b")));
+
+        let pattern = PathPattern::new("/");
+        assert!(pattern.matches(&index!()));
+        assert!(!pattern.matches(&index!("a")));
+    }
+
+    #[test]
+    fn pattern_specificity() {
+        let specificity = |pattern| PathPattern::new(pattern).specificity();
+        assert!(specificity("/services/web/env") > specificity("/services/*/env"));
+        assert!(specificity("/services/*/env") > specificity("/services/**/env"));
+        assert!(specificity("/services/**/env") > specificity("/services/**"));
+        assert!(specificity("/plugins/[0]") > specificity("/plugins/[*]"));
+        assert!(specificity("/**") < specificity("/*"));
+    }
+}
@@ -0,0 +1,263 @@
+use super::union::union_func;
+use super::Merge;
+use crate::index;
+use crate::index::Path as IndexPath;
+use crate::iter::Traverser;
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+
+/// Merged value together with the source that last wrote each of its values.
+///
+/// Sources are recorded for the outermost value written by a source,
+/// e.g. an object that did not exist before or the elements appended to an array.
+/// Use [`Provenance::source`] to look up the source of any nested value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance<L> {
+    pub merged: Value,
+    pub sources: BTreeMap<IndexPath, L>,
+}
+
+impl<L> Provenance<L> {
+    /// Returns the source of the value at `path` or of its closest written parent.
+    #[must_use]
+    pub fn source(&self, path: &IndexPath) -> Option<&L> {
+        (0..=path.len())
+            .rev()
+            .find_map(|len| self.sources.get(&path[..len].iter().cloned().collect()))
+    }
+}
+
+impl<L> std::fmt::Display for Provenance<L>
+where
+    L: std::fmt::Display,
+{
+    /// Writes one line per merged value, annotated with its source:
+    ///
+    /// ```text
+    /// /person/name = "John"  # base
+    /// /cities = ["london","paris"]  # override
+    /// ```
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_value(f, &IndexPath::default(), &self.merged)
+    }
+}
+
+impl<L> Provenance<L>
+where
+    L: std::fmt::Display,
+{
+    fn fmt_value(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        path: &IndexPath,
+        value: &Value,
+    ) -> std::fmt::Result {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let mut key_path = path.clone();
+                    key_path.add(key.clone());
+                    self.fmt_value(f, &key_path, value)?;
+                }
+                Ok(())
+            }
+            value => {
+                write!(f, "{path} = {value}")?;
+                if let Some(source) = self.source(path) {
+                    write!(f, "  # {source}")?;
+                }
+                writeln!(f)
+            }
+        }
+    }
+}
+
+/// Unions all labeled values like [`super::Union::union_all`]
+/// and records which of them wrote each value.
+///
+/// Use `values.iter().enumerate()` to label the sources by their index.
+#[inline]
+#[must_use]
+pub fn union_all_with_provenance<T, L, V>(values: impl IntoIterator<Item = (L, V)>) -> Provenance<L>
+where
+    T: Traverser,
+    L: Clone,
+    V: Borrow<Value>,
+{
+    union_all_with_provenance_by::<T, L, V, _>(values, &mut union_func)
+}
+
+/// Unions all labeled values like [`super::Union::union_all_by`]
+/// and records which of them wrote each value.
+///
+/// A value is written by a source if `union` replaces it with the value of
+/// that source, or appends elements to an array.
+#[must_use]
+pub fn union_all_with_provenance_by<T, L, V, F>(
+    values: impl IntoIterator<Item = (L, V)>,
+    union: &mut F,
+) -> Provenance<L>
+where
+    T: Traverser,
+    L: Clone,
+    V: Borrow<Value>,
+    F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool,
+{
+    let mut merged = Value::Object(Map::default());
+    let mut sources = BTreeMap::new();
+    for (label, value) in values {
+        merged.merge_by_recursive::<T, _>(
+            value.borrow(),
+            &mut |idx: &IndexPath, this: &mut Value, other: Option<&Value>| {
+                if let (Value::Object(_), Some(Value::Object(_))) = (&*this, other) {
+                    // written values are recorded for the children
+                    return union(idx, this, other);
+                }
+                if let Value::Array(values) = &*this {
+                    let len = values.len();
+                    let descend = union(idx, this, other);
+                    match &*this {
+                        Value::Array(values) if values.len() >= len => {
+                            // the existing elements keep their sources
+                            for i in len..values.len() {
+                                sources.insert(idx.clone().join(&index!(i)), label.clone());
+                            }
+                        }
+                        _ => overwrite(&mut sources, idx, &label),
+                    }
+                    return descend;
+                }
+                let replaced = Some(&*this) != other;
+                let descend = union(idx, this, other);
+                if replaced && Some(&*this) == other {
+                    overwrite(&mut sources, idx, &label);
+                } else if this.is_null() {
+                    // null values are only ever written by their first source
+                    sources.entry(idx.clone()).or_insert_with(|| label.clone());
+                }
+                descend
+            },
+        );
+    }
+    Provenance { merged, sources }
+}
+
+/// Records `label` as the source of the value at `idx`, replacing the sources of its children.
+fn overwrite<L: Clone>(sources: &mut BTreeMap<IndexPath, L>, idx: &IndexPath, label: &L) {
+    // children directly follow their parent in the ordered map
+    let children: Vec<IndexPath> = sources
+        .range(idx.clone()..)
+        .map(|(path, _)| path)
+        .take_while(|path| path.starts_with(idx))
+        .cloned()
+        .collect();
+    for path in children {
+        sources.remove(&path);
+    }
+    sources.insert(idx.clone(), label.clone());
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{union_all_with_provenance, Provenance};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn provenance_by_index() {
+        let layers = [
+            json!({ "name": "app", "ports": [80], "db": { "host": "localhost" } }),
+            json!({ "name": "ignored", "ports": [443], "db": { "port": 5432 } }),
+            json!({ "db": { "user": null }, "cache": { "ttl": 60 } }),
+        ];
+        let Provenance { merged, sources } =
+            union_all_with_provenance::<Dfs, _, _>(layers.iter().enumerate());
+        assert_eq!(
+            merged,
+            json!({
+                "name": "app",
+                "ports": [80, 443],
+                "db": { "host": "localhost", "port": 5432, "user": null },
+                "cache": { "ttl": 60 },
+            })
+        );
+        assert_eq!(
+            sources.into_iter().collect::<Vec<_>>(),
+            vec![
+                (index!("cache"), 2),
+                (index!("db"), 0),
+                (index!("db", "port"), 1),
+                (index!("db", "user"), 2),
+                (index!("name"), 0),
+                (index!("ports"), 0),
+                (index!("ports", 1), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn provenance_by_label() {
+        let layers = [
+            (
+                "defaults",
+                json!({ "log": { "level": "info" }, "workers": null }),
+            ),
+            ("file", json!({ "workers": 4, "log": { "format": "json" } })),
+            ("env", json!({ "log": { "level": "debug" } })),
+        ];
+        let provenance = union_all_with_provenance::<Dfs, _, _>(layers);
+        assert_eq!(provenance.source(&index!("workers")), Some(&"file"));
+        assert_eq!(
+            provenance.source(&index!("log", "level")),
+            Some(&"defaults")
+        );
+        assert_eq!(provenance.source(&index!("log", "format")), Some(&"file"));
+        assert_eq!(provenance.source(&index!("log")), Some(&"defaults"));
+        assert_eq!(provenance.source(&index!("missing")), None);
+        assert_eq!(
+            provenance.to_string(),
+            concat!(
+                "/log/level = \"info\"  # defaults\n",
+                "/log/format = \"json\"  # file\n",
+                "/workers = 4  # file\n",
+            )
+        );
+    }
+
+    #[test]
+    fn provenance_of_nested_values() {
+        let layers = [
+            ("base", json!({ "a": null })),
+            ("override", json!({ "a": { "b": { "c": 1 } } })),
+        ];
+        let provenance = union_all_with_provenance::<Dfs, _, _>(layers);
+        assert_eq!(
+            provenance.sources.into_iter().collect::<Vec<_>>(),
+            vec![(index!("a"), "override")]
+        );
+    }
+
+    #[test]
+    fn provenance_of_appended_elements() {
+        let layers = [
+            ("base", json!({ "list": [{ "a": 1 }] })),
+            ("first", json!({ "list": [2, 3] })),
+            ("second", json!({ "list": [4] })),
+        ];
+        let provenance = union_all_with_provenance::<Dfs, _, _>(layers);
+        assert_eq!(provenance.merged, json!({ "list": [{ "a": 1 }, 2, 3, 4] }));
+        assert_eq!(
+            provenance.sources.into_iter().collect::<Vec<_>>(),
+            vec![
+                (index!("list"), "base"),
+                (index!("list", 1), "first"),
+                (index!("list", 2), "first"),
+                (index!("list", 3), "second"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::options::ArrayStrategy;
+use super::MergeControl;
+use crate::index::Path as IndexPath;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub type RuleFunc =
+    Arc<dyn Fn(&IndexPath, &mut Value, Option<&Value>) -> MergeControl + Send + Sync>;
+
+/// Merge behavior for paths matching a [`PathPattern`](super::PathPattern).
+///
+/// Rules are added using [`MergeOptions::rule`](super::MergeOptions::rule).
+#[derive(Clone)]
+pub enum Rule {
+    /// Replace the value with the other value, without merging them.
+    Overwrite,
+    /// Keep the existing value and only add the other value if it is missing.
+    KeepBase,
+    /// Ignore the other value.
+    Skip,
+    /// Merge using a custom merge function.
+    ///
+    /// Like the callbacks of [`Merge::merge_by_control`](super::Merge::merge_by_control),
+    /// the function returns a [`MergeControl`] to skip the children or siblings
+    /// of the value or to stop merging.
+    Custom(RuleFunc),
+    /// Merge arrays using the given strategy.
+    ///
+    /// Other values are merged as usual.
+    Array(ArrayStrategy),
+}
+
+impl Rule {
+    /// Creates a [`Rule::Custom`] from a function returning a [`MergeControl`],
+    /// or a `bool` indicating if the children of the value should be merged.
+    #[inline]
+    #[must_use]
+    pub fn custom<C>(
+        func: impl Fn(&IndexPath, &mut Value, Option<&Value>) -> C + Send + Sync + 'static,
+    ) -> Self
+    where
+        C: Into<MergeControl>,
+    {
+        Self::Custom(Arc::new(move |idx, this, other| {
+            func(idx, this, other).into()
+        }))
+    }
+}
+
+impl std::fmt::Debug for Rule {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overwrite => f.write_str("Overwrite"),
+            Self::KeepBase => f.write_str("KeepBase"),
+            Self::Skip => f.write_str("Skip"),
+            Self::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+            Self::Array(strategy) => f.debug_tuple("Array").field(strategy).finish(),
+        }
+    }
+}
@@ -0,0 +1,577 @@
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::sync::Arc;
+
+pub type LeafEq = Arc<dyn Fn(&Value, &Value) -> bool + Send + Sync>;
+
+/// How arrays are treated by set operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ArraySemantics {
+    /// Elements are compared with the element at the same index of the other array.
+    ///
+    /// Elements are removed if nothing of them remains, shifting later elements.
+    Positional,
+    /// Elements are matched with equal elements of the other array,
+    /// where each element can only be matched once.
+    #[default]
+    Multiset,
+    /// Elements are matched with equal elements of the other array,
+    /// ignoring duplicates.
+    Set,
+}
+
+/// Options for the set operations [`Intersect`] and [`Difference`].
+#[derive(Clone, Default)]
+pub struct SetOptions {
+    array_semantics: ArraySemantics,
+    leaf_eq: Option<LeafEq>,
+}
+
+impl std::fmt::Debug for SetOptions {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SetOptions")
+            .field("array_semantics", &self.array_semantics)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SetOptions {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn array_semantics(mut self, semantics: ArraySemantics) -> Self {
+        self.array_semantics = semantics;
+        self
+    }
+
+    /// Sets the function used to compare values that are neither objects nor arrays.
+    ///
+    /// Defaults to `==`.
+    #[inline]
+    #[must_use]
+    pub fn leaf_eq(mut self, eq: impl Fn(&Value, &Value) -> bool + Send + Sync + 'static) -> Self {
+        self.leaf_eq = Some(Arc::new(eq));
+        self
+    }
+
+    fn leaves_eq(&self, left: &Value, right: &Value) -> bool {
+        self.leaf_eq
+            .as_ref()
+            .map_or_else(|| left == right, |eq| eq(left, right))
+    }
+
+    /// Checks if two values are deeply equal using the leaf equality.
+    fn values_eq(&self, left: &Value, right: &Value) -> bool {
+        match (left, right) {
+            (Value::Object(left), Value::Object(right)) => {
+                left.len() == right.len()
+                    && left.iter().all(|(key, left)| {
+                        right
+                            .get(key)
+                            .is_some_and(|right| self.values_eq(left, right))
+                    })
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(left, right)| self.values_eq(left, right))
+            }
+            (Value::Object(_) | Value::Array(_), _) | (_, Value::Object(_) | Value::Array(_)) => {
+                false
+            }
+            (left, right) => self.leaves_eq(left, right),
+        }
+    }
+
+    fn contains(&self, values: &[Value], value: &Value) -> bool {
+        values.iter().any(|other| self.values_eq(value, other))
+    }
+
+    /// Returns the elements of `values` without duplicates.
+    fn unique<'a>(&self, values: &'a [Value]) -> Vec<&'a Value> {
+        let mut unique: Vec<&Value> = Vec::new();
+        for value in values {
+            if !unique.iter().any(|other| self.values_eq(value, other)) {
+                unique.push(value);
+            }
+        }
+        unique
+    }
+
+    /// Returns the parts of `left` that are also in `right`.
+    fn intersection(&self, left: &Value, right: &Value) -> Option<Value> {
+        match (left, right) {
+            (Value::Object(left), Value::Object(right)) => {
+                let map = left
+                    .iter()
+                    .filter_map(|(key, left)| {
+                        let value = self.intersection(left, right.get(key)?)?;
+                        Some((key.clone(), value))
+                    })
+                    .collect::<Map<_, _>>();
+                Some(Value::Object(map))
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                let values = match self.array_semantics {
+                    ArraySemantics::Positional => left
+                        .iter()
+                        .zip(right)
+                        .filter_map(|(left, right)| self.intersection(left, right))
+                        .collect(),
+                    ArraySemantics::Multiset => {
+                        let mut remaining: Vec<&Value> = right.iter().collect();
+                        left.iter()
+                            .filter(|left| {
+                                let pos = remaining
+                                    .iter()
+                                    .position(|right| self.values_eq(left, right));
+                                pos.map(|pos| remaining.swap_remove(pos)).is_some()
+                            })
+                            .cloned()
+                            .collect()
+                    }
+                    ArraySemantics::Set => self
+                        .unique(left)
+                        .into_iter()
+                        .filter(|left| self.contains(right, left))
+                        .cloned()
+                        .collect(),
+                };
+                Some(Value::Array(values))
+            }
+            (left, right) if self.values_eq(left, right) => Some(left.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the parts of `left` that are not in `right`.
+    fn difference(&self, left: &Value, right: &Value) -> Option<Value> {
+        match (left, right) {
+            (Value::Object(left), Value::Object(right)) => {
+                let map = left
+                    .iter()
+                    .filter_map(|(key, left)| {
+                        let value = match right.get(key) {
+                            Some(right) => self.difference(left, right)?,
+                            None => left.clone(),
+                        };
+                        Some((key.clone(), value))
+                    })
+                    .collect::<Map<_, _>>();
+                (!map.is_empty()).then_some(Value::Object(map))
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                let values: Vec<Value> = match self.array_semantics {
+                    ArraySemantics::Positional => left
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(idx, left)| match right.get(idx) {
+                            Some(right) => self.difference(left, right),
+                            None => Some(left.clone()),
+                        })
+                        .collect(),
+                    ArraySemantics::Multiset => {
+                        let mut remaining: Vec<&Value> = right.iter().collect();
+                        left.iter()
+                            .filter(|left| {
+                                let pos = remaining
+                                    .iter()
+                                    .position(|right| self.values_eq(left, right));
+                                pos.map(|pos| remaining.swap_remove(pos)).is_none()
+                            })
+                            .cloned()
+                            .collect()
+                    }
+                    ArraySemantics::Set => self
+                        .unique(left)
+                        .into_iter()
+                        .filter(|left| !self.contains(right, left))
+                        .cloned()
+                        .collect(),
+                };
+                (!values.is_empty()).then_some(Value::Array(values))
+            }
+            (left, right) if self.values_eq(left, right) => None,
+            (left, _) => Some(left.clone()),
+        }
+    }
+
+    /// Returns the parts of `left` and `right` that are not in both.
+    ///
+    /// If the values at a path differ, the value of `left` is used.
+    fn symmetric_difference(&self, left: &Value, right: &Value) -> Option<Value> {
+        match (left, right) {
+            (Value::Object(left), Value::Object(right)) => {
+                let mut map = left
+                    .iter()
+                    .filter_map(|(key, left)| {
+                        let value = match right.get(key) {
+                            Some(right) => self.symmetric_difference(left, right)?,
+                            None => left.clone(),
+                        };
+                        Some((key.clone(), value))
+                    })
+                    .collect::<Map<_, _>>();
+                for (key, right) in right {
+                    if !left.contains_key(key) {
+                        map.insert(key.clone(), right.clone());
+                    }
+                }
+                (!map.is_empty()).then_some(Value::Object(map))
+            }
+            (Value::Array(left_values), Value::Array(right_values)) => {
+                let values: Vec<Value> = match self.array_semantics {
+                    ArraySemantics::Positional => (0..left_values.len().max(right_values.len()))
+                        .filter_map(|idx| match (left_values.get(idx), right_values.get(idx)) {
+                            (Some(left), Some(right)) => self.symmetric_difference(left, right),
+                            (value, other) => value.or(other).cloned(),
+                        })
+                        .collect(),
+                    ArraySemantics::Multiset | ArraySemantics::Set => {
+                        let mut values = into_array(self.difference(left, right));
+                        values.extend(into_array(self.difference(right, left)));
+                        values
+                    }
+                };
+                (!values.is_empty()).then_some(Value::Array(values))
+            }
+            (left, right) if self.values_eq(left, right) => None,
+            (left, _) => Some(left.clone()),
+        }
+    }
+}
+
+fn into_array(value: Option<Value>) -> Vec<Value> {
+    match value {
+        Some(Value::Array(values)) => values,
+        _ => Vec::new(),
+    }
+}
+
+pub trait Intersect: Sized {
+    /// Intersects all values, or returns `null` if there are none.
+    #[must_use]
+    fn intersect_all<V>(values: impl IntoIterator<Item = V>, options: &SetOptions) -> Self
+    where
+        V: Borrow<Value>;
+
+    /// Recursively keeps only the parts of the value that are also in `other`.
+    ///
+    /// Objects keep the keys present in both objects, and arrays are intersected
+    /// according to their [`ArraySemantics`]. Objects and arrays are kept even if
+    /// they become empty, e.g. intersecting objects without common keys results in `{}`.
+    /// Other values that are not equal become `null`.
+    fn intersect(&mut self, other: &Self, options: &SetOptions);
+
+    #[inline]
+    #[must_use]
+    fn into_intersection(mut self, other: &Self, options: &SetOptions) -> Self {
+        self.intersect(other, options);
+        self
+    }
+}
+
+pub trait Difference: Sized {
+    /// Removes all following values from the first value,
+    /// or returns `null` if there are none.
+    #[must_use]
+    fn difference_all<V>(values: impl IntoIterator<Item = V>, options: &SetOptions) -> Self
+    where
+        V: Borrow<Value>;
+
+    /// Computes the symmetric difference of all values,
+    /// or returns `null` if there are none.
+    #[must_use]
+    fn symmetric_difference_all<V>(
+        values: impl IntoIterator<Item = V>,
+        options: &SetOptions,
+    ) -> Self
+    where
+        V: Borrow<Value>;
+
+    /// Recursively removes the parts of the value that are also in `other`.
+    ///
+    /// Objects keep the keys that are missing or different in `other`,
+    /// and arrays keep the elements that are not in `other` according to their
+    /// [`ArraySemantics`]. Objects and arrays that become empty are removed,
+    /// and if nothing is left, the value becomes `null`.
+    fn difference(&mut self, other: &Self, options: &SetOptions);
+
+    /// Recursively keeps only the parts that are in either the value or `other`, but not both.
+    ///
+    /// Values that differ between both keep the value of `self`.
+    /// If nothing is left, the value becomes `null`.
+    fn symmetric_difference(&mut self, other: &Self, options: &SetOptions);
+
+    #[inline]
+    #[must_use]
+    fn into_difference(mut self, other: &Self, options: &SetOptions) -> Self {
+        self.difference(other, options);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    fn into_symmetric_difference(mut self, other: &Self, options: &SetOptions) -> Self {
+        self.symmetric_difference(other, options);
+        self
+    }
+}
+
+impl Intersect for Value {
+    #[inline]
+    fn intersect_all<V>(values: impl IntoIterator<Item = V>, options: &SetOptions) -> Self
+    where
+        V: Borrow<Value>,
+    {
+        fold(values, |result, value| result.intersect(value, options))
+    }
+
+    #[inline]
+    fn intersect(&mut self, other: &Self, options: &SetOptions) {
+        *self = options.intersection(self, other).unwrap_or_default();
+    }
+}
+
+impl Difference for Value {
+    #[inline]
+    fn difference_all<V>(values: impl IntoIterator<Item = V>, options: &SetOptions) -> Self
+    where
+        V: Borrow<Value>,
+    {
+        fold(values, |result, value| result.difference(value, options))
+    }
+
+    #[inline]
+    fn symmetric_difference_all<V>(
+        values: impl IntoIterator<Item = V>,
+        options: &SetOptions,
+    ) -> Self
+    where
+        V: Borrow<Value>,
+    {
+        fold(values, |result, value| {
+            result.symmetric_difference(value, options);
+        })
+    }
+
+    #[inline]
+    fn difference(&mut self, other: &Self, options: &SetOptions) {
+        *self = options.difference(self, other).unwrap_or_default();
+    }
+
+    #[inline]
+    fn symmetric_difference(&mut self, other: &Self, options: &SetOptions) {
+        *self = options
+            .symmetric_difference(self, other)
+            .unwrap_or_default();
+    }
+}
+
+fn fold<V>(values: impl IntoIterator<Item = V>, mut func: impl FnMut(&mut Value, &Value)) -> Value
+where
+    V: Borrow<Value>,
+{
+    let mut values = values.into_iter();
+    let Some(first) = values.next() else {
+        return Value::Null;
+    };
+    let mut result = first.borrow().clone();
+    for value in values {
+        func(&mut result, value.borrow());
+    }
+    result
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{ArraySemantics, Difference, Intersect, SetOptions};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn intersect_objects() {
+        let left = json!({
+            "a": 1,
+            "b": { "c": "d", "e": "f" },
+            "g": [1, 2],
+            "h": "only left",
+            "i": "differs",
+        });
+        let right = json!({
+            "a": 1,
+            "b": { "c": "d", "x": "y" },
+            "g": [2, 3],
+            "i": "different",
+            "j": "only right",
+        });
+        assert_eq!(
+            left.into_intersection(&right, &SetOptions::new()),
+            json!({ "a": 1, "b": { "c": "d" }, "g": [2] })
+        );
+        assert_eq!(
+            json!(1).into_intersection(&json!(2), &SetOptions::new()),
+            Value::Null
+        );
+        assert_eq!(
+            json!({ "a": 1, "b": { "c": 1 } })
+                .into_intersection(&json!({ "b": { "d": 1 }, "e": 1 }), &SetOptions::new()),
+            json!({ "b": {} })
+        );
+        assert_eq!(
+            json!({ "a": 1 }).into_intersection(&json!({ "b": 1 }), &SetOptions::new()),
+            json!({})
+        );
+    }
+
+    #[test]
+    fn intersect_arrays() {
+        let left = json!([1, 1, 2, { "a": 1, "b": 2 }, 3]);
+        let right = json!([1, 2, 2, { "a": 1 }, { "b": 2, "a": 1 }]);
+        let intersection = |semantics| {
+            left.clone()
+                .into_intersection(&right, &SetOptions::new().array_semantics(semantics))
+        };
+        assert_eq!(
+            intersection(ArraySemantics::Positional),
+            json!([1, 2, { "a": 1 }])
+        );
+        assert_eq!(
+            intersection(ArraySemantics::Multiset),
+            json!([1, 2, { "a": 1, "b": 2 }])
+        );
+        assert_eq!(
+            json!([1, 1, 2]).into_intersection(
+                &json!([1, 1, 1]),
+                &SetOptions::new().array_semantics(ArraySemantics::Set)
+            ),
+            json!([1])
+        );
+        assert_eq!(
+            json!([1, 1, 2]).into_intersection(&json!([1, 1, 1]), &SetOptions::new()),
+            json!([1, 1])
+        );
+    }
+
+    #[test]
+    fn difference_objects() {
+        let left = json!({
+            "a": 1,
+            "b": { "c": "d", "e": "f" },
+            "g": [1, 2],
+            "h": "only left",
+            "i": "differs",
+            "k": { "l": 1 },
+        });
+        let right = json!({
+            "a": 1,
+            "b": { "c": "d", "x": "y" },
+            "g": [2, 3],
+            "i": "different",
+            "j": "only right",
+            "k": { "l": 1 },
+        });
+        assert_eq!(
+            left.clone().into_difference(&right, &SetOptions::new()),
+            json!({ "b": { "e": "f" }, "g": [1], "h": "only left", "i": "differs" })
+        );
+        assert_eq!(
+            left.clone()
+                .into_symmetric_difference(&right, &SetOptions::new()),
+            json!({
+                "b": { "e": "f", "x": "y" },
+                "g": [1, 3],
+                "h": "only left",
+                "i": "differs",
+                "j": "only right",
+            })
+        );
+        assert_eq!(
+            left.clone().into_difference(&left, &SetOptions::new()),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn difference_arrays() {
+        let options = |semantics| SetOptions::new().array_semantics(semantics);
+        let (left, right) = (json!([1, 1, 2, 3]), json!([1, 3, 4]));
+        assert_eq!(
+            left.clone()
+                .into_difference(&right, &options(ArraySemantics::Positional)),
+            json!([1, 2, 3])
+        );
+        assert_eq!(
+            left.clone()
+                .into_difference(&right, &options(ArraySemantics::Multiset)),
+            json!([1, 2])
+        );
+        assert_eq!(
+            left.clone()
+                .into_difference(&right, &options(ArraySemantics::Set)),
+            json!([2])
+        );
+        assert_eq!(
+            left.clone()
+                .into_symmetric_difference(&right, &options(ArraySemantics::Positional)),
+            json!([1, 2, 3])
+        );
+        assert_eq!(
+            left.clone()
+                .into_symmetric_difference(&right, &options(ArraySemantics::Multiset)),
+            json!([1, 2, 4])
+        );
+    }
+
+    #[test]
+    fn set_operations_all() {
+        let values = [
+            json!({ "a": 1, "b": 2, "c": [1, 2, 3] }),
+            json!({ "a": 1, "b": 3, "c": [2, 3] }),
+            json!({ "a": 1, "c": [3, 4], "d": 4 }),
+        ];
+        let options = SetOptions::new();
+        assert_eq!(
+            Value::intersect_all(&values, &options),
+            json!({ "a": 1, "c": [3] })
+        );
+        assert_eq!(
+            Value::difference_all(&values, &options),
+            json!({ "b": 2, "c": [1] })
+        );
+        assert_eq!(
+            Value::symmetric_difference_all(&values, &options),
+            json!({ "a": 1, "b": 2, "c": [1, 3, 4], "d": 4 })
+        );
+        assert_eq!(
+            Value::intersect_all(Vec::<Value>::new(), &options),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn set_operations_leaf_eq() {
+        let options = SetOptions::new().leaf_eq(|left, right| match (left, right) {
+            (Value::String(left), Value::String(right)) => left.eq_ignore_ascii_case(right),
+            (left, right) => left == right,
+        });
+        let left = json!({ "env": "PROD", "tags": ["A", "b"] });
+        let right = json!({ "env": "prod", "tags": ["a", "c"] });
+        assert_eq!(
+            left.clone().into_intersection(&right, &options),
+            json!({ "env": "PROD", "tags": ["A"] })
+        );
+        assert_eq!(
+            left.into_difference(&right, &options),
+            json!({ "tags": ["b"] })
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use super::error::{Error, ValueKind};
+use crate::index::{is_integer, split_path, Kind as IndexKind, Path as IndexPath};
+use crate::pointer;
+use serde_json::{Map, Value};
+
+/// Converts a `/` separated path into an index path.
+///
+/// Like in [`Index::get_path`](crate::index::Index::get_path), paths starting with `/`
+/// are JSON pointers. Integer segments are array indices if they refer to an
+/// existing array, and object keys otherwise.
+pub(crate) fn resolve_path(value: &Value, path: &str) -> Result<IndexPath, Error> {
+    if !path.starts_with('/') {
+        return Ok(resolve_segments(value, split_path(path)));
+    }
+    let pointer = IndexPath::from_pointer(path).map_err(Error::InvalidPointer)?;
+    Ok(pointer::resolve(value, &pointer))
+}
+
+fn resolve_segments<'a>(value: &Value, segments: impl Iterator<Item = &'a str>) -> IndexPath {
+    let mut current = Some(value);
+    let mut idx = IndexPath::empty();
+    for segment in segments {
+        match current {
+            Some(Value::Array(values)) if is_integer(segment) => {
+                let Ok(arr_idx) = segment.parse::<usize>() else {
+                    idx.add(segment.to_string());
+                    current = None;
+                    continue;
+                };
+                idx.add(arr_idx);
+                current = values.get(arr_idx);
+            }
+            _ => {
+                idx.add(segment.to_string());
+                current = current.and_then(|value| value.get(segment));
+            }
+        }
+    }
+    idx
+}
+
+/// Returns the value at `idx`, creating missing objects and arrays along the way.
+///
+/// `null` values are replaced by an object or array depending on the index,
+/// and the index after the last element of an array appends a `null` value.
+pub(crate) fn get_or_insert<'a>(
+    mut value: &'a mut Value,
+    idx: &IndexPath,
+) -> Result<&'a mut Value, Error> {
+    for (depth, index) in idx.iter().enumerate() {
+        let path = || idx[..depth].iter().cloned().collect();
+        let mismatch = |this: &Value, other| Error::TypeMismatch {
+            path: path(),
+            this: ValueKind::from(this),
+            other,
+        };
+        value = match index.kind() {
+            IndexKind::ObjectKey(key) => {
+                if value.is_null() {
+                    *value = Value::Object(Map::default());
+                }
+                match value {
+                    Value::Object(map) => map.entry(key).or_insert(Value::Null),
+                    this => return Err(mismatch(this, ValueKind::Object)),
+                }
+            }
+            IndexKind::ArrayIndex(arr_idx) => {
+                if value.is_null() {
+                    *value = Value::Array(Vec::new());
+                }
+                match value {
+                    Value::Array(values) => {
+                        if *arr_idx > values.len() {
+                            return Err(Error::IndexOutOfRange {
+                                path: path(),
+                                index: *arr_idx,
+                                len: values.len(),
+                            });
+                        }
+                        if *arr_idx == values.len() {
+                            values.push(Value::Null);
+                        }
+                        &mut values[*arr_idx]
+                    }
+                    this => return Err(mismatch(this, ValueKind::Array)),
+                }
+            }
+        };
+    }
+    Ok(value)
+}
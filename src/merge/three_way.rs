@@ -0,0 +1,297 @@
+use crate::index::{Index, Path as IndexPath};
+use crate::iter::Traverser;
+use serde_json::Value;
+
+/// Policy for resolving conflicting changes in a three-way merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Resolution {
+    /// Fail if there are any conflicts.
+    #[default]
+    Fail,
+    /// Resolve conflicts using our changes.
+    PreferOurs,
+    /// Resolve conflicts using their changes.
+    PreferTheirs,
+}
+
+/// Conflicting changes of both sides.
+///
+/// Values are `None` if they do not exist (e.g. have been removed).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: IndexPath,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// Result of a successful three-way merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreeWayMerge {
+    pub merged: Value,
+    /// Conflicts that were resolved according to the [`Resolution`] policy.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Error returned when a three-way merge has conflicts and
+/// [`Resolution::Fail`] is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictError {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl std::fmt::Display for ConflictError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} merge conflict(s) at {}",
+            self.conflicts.len(),
+            self.conflicts
+                .iter()
+                .map(|conflict| conflict.path.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
+/// Merges the changes from `base` to `ours` and from `base` to `theirs`.
+///
+/// Changes made by only one side are applied automatically.
+/// Objects changed by both sides are merged key by key,
+/// while other values (including arrays) conflict if both sides
+/// changed them differently.
+///
+/// # Errors
+///
+/// Returns the list of conflicts if there are any and [`Resolution::Fail`] is used.
+pub fn merge_three_way<T>(
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+    resolution: Resolution,
+) -> Result<ThreeWayMerge, ConflictError>
+where
+    T: Traverser,
+{
+    let mut merged = ours.clone();
+    let mut conflicts = Vec::new();
+    let mut traverser = T::new();
+    traverser.set_limit(None);
+    traverser.set_depth(None);
+    while traverser
+        .process_next(theirs, |idx, their_value| {
+            let base_value = base.get_index(idx);
+            let our_value = ours.get_index(idx);
+            if their_value == our_value || their_value == base_value {
+                // same change on both sides or only changed by us
+                return false;
+            }
+            if our_value == base_value {
+                // only changed by them
+                if let Some(their_value) = their_value {
+                    merged[idx] = their_value.clone();
+                }
+                return false;
+            }
+            match (our_value, their_value) {
+                (Some(Value::Object(our_map)), Some(Value::Object(their_map))) => {
+                    // merge key by key, handling keys removed by them here
+                    let removed = our_map.keys().filter(|key| !their_map.contains_key(*key));
+                    for key in removed {
+                        let mut key_idx = idx.clone();
+                        key_idx.add(key.clone());
+                        let Some(base_value) = base.get_index(&key_idx) else {
+                            // added by us
+                            continue;
+                        };
+                        let our_value = &our_map[key];
+                        if our_value != base_value {
+                            conflicts.push(Conflict {
+                                path: key_idx,
+                                base: Some(base_value.clone()),
+                                ours: Some(our_value.clone()),
+                                theirs: None,
+                            });
+                            if resolution != Resolution::PreferTheirs {
+                                continue;
+                            }
+                        }
+                        if let Some(Value::Object(merged_map)) = merged.get_index_mut(idx) {
+                            merged_map.shift_remove(key);
+                        }
+                    }
+                    true
+                }
+                (our_value, their_value) => {
+                    conflicts.push(Conflict {
+                        path: idx.clone(),
+                        base: base_value.cloned(),
+                        ours: our_value.cloned(),
+                        theirs: their_value.cloned(),
+                    });
+                    if resolution == Resolution::PreferTheirs {
+                        if let Some(their_value) = their_value {
+                            merged[idx] = their_value.clone();
+                        }
+                    }
+                    false
+                }
+            }
+        })
+        .is_some()
+    {}
+
+    if resolution == Resolution::Fail && !conflicts.is_empty() {
+        return Err(ConflictError { conflicts });
+    }
+    Ok(ThreeWayMerge { merged, conflicts })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{merge_three_way, Conflict, ConflictError, Resolution, ThreeWayMerge};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn three_way_merge_without_conflicts() {
+        let base = json!({
+            "name": "app",
+            "replicas": 1,
+            "labels": { "team": "a", "tier": "web" },
+            "ports": [80],
+            "debug": true,
+        });
+        let ours = json!({
+            "name": "app",
+            "replicas": 3,
+            "labels": { "team": "a", "tier": "web", "env": "prod" },
+            "ports": [80],
+        });
+        let theirs = json!({
+            "name": "app-v2",
+            "replicas": 1,
+            "labels": { "team": "b" },
+            "ports": [80, 443],
+            "debug": true,
+            "image": "app:2",
+        });
+        assert_eq!(
+            merge_three_way::<Dfs>(&base, &ours, &theirs, Resolution::Fail),
+            Ok(ThreeWayMerge {
+                merged: json!({
+                    "name": "app-v2",
+                    "replicas": 3,
+                    "labels": { "team": "b", "env": "prod" },
+                    "ports": [80, 443],
+                    "image": "app:2",
+                }),
+                conflicts: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn three_way_merge_conflicts() {
+        let base = json!({
+            "replicas": 1,
+            "ports": [80],
+            "labels": { "team": "a", "tier": "web" },
+            "debug": true,
+        });
+        let ours = json!({
+            "replicas": 3,
+            "ports": [80, 8080],
+            "labels": { "team": "b", "tier": "db" },
+        });
+        let theirs = json!({
+            "replicas": 2,
+            "ports": [80, 443],
+            "labels": { "team": "c" },
+            "debug": false,
+        });
+        let conflicts = vec![
+            Conflict {
+                path: index!("replicas"),
+                base: Some(json!(1)),
+                ours: Some(json!(3)),
+                theirs: Some(json!(2)),
+            },
+            Conflict {
+                path: index!("ports"),
+                base: Some(json!([80])),
+                ours: Some(json!([80, 8080])),
+                theirs: Some(json!([80, 443])),
+            },
+            Conflict {
+                path: index!("labels", "tier"),
+                base: Some(json!("web")),
+                ours: Some(json!("db")),
+                theirs: None,
+            },
+            Conflict {
+                path: index!("labels", "team"),
+                base: Some(json!("a")),
+                ours: Some(json!("b")),
+                theirs: Some(json!("c")),
+            },
+            Conflict {
+                path: index!("debug"),
+                base: Some(json!(true)),
+                ours: None,
+                theirs: Some(json!(false)),
+            },
+        ];
+
+        let err = merge_three_way::<Dfs>(&base, &ours, &theirs, Resolution::Fail).unwrap_err();
+        assert_eq!(
+            err,
+            ConflictError {
+                conflicts: conflicts.clone()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "5 merge conflict(s) at /replicas, /ports, /labels/tier, /labels/team, /debug"
+        );
+
+        assert_eq!(
+            merge_three_way::<Dfs>(&base, &ours, &theirs, Resolution::PreferOurs),
+            Ok(ThreeWayMerge {
+                merged: ours.clone(),
+                conflicts: conflicts.clone(),
+            })
+        );
+        assert_eq!(
+            merge_three_way::<Dfs>(&base, &ours, &theirs, Resolution::PreferTheirs),
+            Ok(ThreeWayMerge {
+                merged: json!({
+                    "replicas": 2,
+                    "ports": [80, 443],
+                    "labels": { "team": "c" },
+                    "debug": false,
+                }),
+                conflicts,
+            })
+        );
+    }
+
+    #[test]
+    fn three_way_merge_scalars() {
+        let merge = |base, ours, theirs| {
+            merge_three_way::<Dfs>(&base, &ours, &theirs, Resolution::Fail)
+                .map(|result| result.merged)
+        };
+        assert_eq!(merge(json!(1), json!(2), json!(1)), Ok(json!(2)));
+        assert_eq!(merge(json!(1), json!(1), json!(2)), Ok(json!(2)));
+        assert_eq!(merge(json!(1), json!(2), json!(2)), Ok(json!(2)));
+        assert!(merge(json!(1), json!(2), json!(3)).is_err());
+        assert!(merge(json!({}), json!([]), json!({"a": 1})).is_err());
+    }
+}
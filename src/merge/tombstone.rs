@@ -0,0 +1,281 @@
+use super::MergeControl;
+use crate::index::{Index, Path as IndexPath};
+use serde_json::{json, Value};
+
+/// Marker value that deletes the corresponding value when merging.
+///
+/// Object values equal to the marker remove the key from the base object,
+/// and array elements equal to the marker remove the base element at the same index.
+/// Markers are never merged into the base value.
+///
+/// Use [`MergeOptions::tombstone`](super::MergeOptions::tombstone) when merging with
+/// [`Merge::merge_with`](super::Merge::merge_with), or wrap a merge function using
+/// [`Tombstone::wrap`], e.g. for [`Union::union_all_by`](super::Union::union_all_by).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tombstone {
+    marker: Value,
+}
+
+impl Default for Tombstone {
+    #[inline]
+    fn default() -> Self {
+        Self::new(json!({ "$delete": true }))
+    }
+}
+
+impl Tombstone {
+    #[inline]
+    #[must_use]
+    pub fn new(marker: Value) -> Self {
+        Self { marker }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn marker(&self) -> &Value {
+        &self.marker
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_tombstone(&self, value: &Value) -> bool {
+        value == &self.marker
+    }
+
+    /// Removes all markers from `value`.
+    pub fn strip(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|_, value| !self.is_tombstone(value));
+                for value in map.values_mut() {
+                    self.strip(value);
+                }
+            }
+            Value::Array(values) => {
+                values.retain(|value| !self.is_tombstone(value));
+                for value in values {
+                    self.strip(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Wraps `merge` so that markers in the other value delete values.
+    ///
+    /// ```
+    /// use serde_json::{json, Value};
+    /// use serde_json_merge::merge::{union_func, Tombstone};
+    /// use serde_json_merge::{Dfs, Union};
+    ///
+    /// let tombstone = Tombstone::default();
+    /// let layers = [
+    ///     json!({ "a": 1, "b": 2 }),
+    ///     json!({ "a": { "$delete": true }, "c": 3 }),
+    /// ];
+    /// let union: Value = Union::union_all_by::<Dfs, _, _>(&layers, &mut tombstone.wrap(union_func));
+    /// assert_eq!(union, json!({ "b": 2, "c": 3 }));
+    /// ```
+    #[inline]
+    pub fn wrap<'a, F>(
+        &'a self,
+        mut merge: F,
+    ) -> impl FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool + 'a
+    where
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool + 'a,
+    {
+        move |idx, this, other| self.merge(idx, this, other, &mut merge) == MergeControl::Descend
+    }
+
+    /// Deletes the values marked in `other` before merging the remaining values using `merge`.
+    pub(crate) fn merge<F, C>(
+        &self,
+        idx: &IndexPath,
+        this: &mut Value,
+        other: Option<&Value>,
+        merge: &mut F,
+    ) -> MergeControl
+    where
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> C,
+        C: Into<MergeControl>,
+    {
+        let Some(other) = other else {
+            return merge(idx, this, other).into();
+        };
+        if self.is_tombstone(other) {
+            *this = Value::Null;
+            return MergeControl::Skip;
+        }
+        let was_null = this.is_null();
+        let control = match (&mut *this, other) {
+            (Value::Object(map), Value::Object(other_map))
+                if other_map.values().any(|value| self.is_tombstone(value)) =>
+            {
+                let mut remaining = other_map.clone();
+                remaining.retain(|key, value| {
+                    let deleted = self.is_tombstone(value);
+                    if deleted {
+                        map.shift_remove(key);
+                    }
+                    !deleted
+                });
+                merge(idx, this, Some(&Value::Object(remaining))).into()
+            }
+            (Value::Array(values), Value::Array(other_values))
+                if other_values.iter().any(|value| self.is_tombstone(value)) =>
+            {
+                let deleted = other_values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| self.is_tombstone(value))
+                    .map(|(arr_idx, _)| arr_idx);
+                for arr_idx in deleted.rev() {
+                    if arr_idx < values.len() {
+                        values.remove(arr_idx);
+                    }
+                }
+                let mut remaining = other_values.clone();
+                remaining.retain(|value| !self.is_tombstone(value));
+                let remaining = Value::Array(remaining);
+                match merge(idx, this, Some(&remaining)).into() {
+                    // the traversal would merge the elements by their index in `other`,
+                    // which no longer match the indices of `this` after the deletions
+                    MergeControl::Descend => self.merge_children(idx, this, &remaining, merge),
+                    control => control,
+                }
+            }
+            _ => merge(idx, this, Some(other)).into(),
+        };
+        if was_null && control != MergeControl::Descend {
+            // values that did not exist yet may have been copied including markers
+            self.strip(this);
+        }
+        control
+    }
+
+    /// Recursively merges the children of `other` into the children of `this`,
+    /// returning [`MergeControl::Skip`] unless merging was stopped.
+    fn merge_children<F, C>(
+        &self,
+        idx: &IndexPath,
+        this: &mut Value,
+        other: &Value,
+        merge: &mut F,
+    ) -> MergeControl
+    where
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> C,
+        C: Into<MergeControl>,
+    {
+        let children: Vec<(IndexPath, &Value)> = match other {
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, value)| (IndexPath::new(key.clone()), value))
+                .collect(),
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .map(|(arr_idx, value)| (IndexPath::new(arr_idx), value))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (child, other) in children {
+            let Some(this) = this.get_index_mut(&child) else {
+                continue;
+            };
+            let child_idx = idx.clone().join(&child);
+            let control = match self.merge(&child_idx, this, Some(other), merge) {
+                MergeControl::Descend => self.merge_children(&child_idx, this, other, merge),
+                control => control,
+            };
+            match control {
+                MergeControl::Descend | MergeControl::Skip => {}
+                MergeControl::SkipSiblings => break,
+                MergeControl::Stop => return MergeControl::Stop,
+            }
+        }
+        MergeControl::Skip
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::Tombstone;
+    use crate::iter::dfs::Dfs;
+    use crate::merge::union::union_func;
+    use crate::merge::{ArrayStrategy, Merge, MergeOptions, Union};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn merge_with_tombstone() {
+        let base = json!({
+            "a": 1,
+            "b": { "c": 2, "d": 3 },
+            "list": [1, 2, 3],
+            "keep": true,
+        });
+        let merge = json!({
+            "a": { "$delete": true },
+            "b": { "c": { "$delete": true }, "e": 4 },
+            "list": [{ "$delete": true }, 5, { "$delete": true }],
+            "new": { "f": { "$delete": true }, "g": [{ "$delete": true }, 6] },
+            "missing": { "$delete": true },
+        });
+        let options = MergeOptions::new().tombstone(Tombstone::default());
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({
+                "b": { "d": 3, "e": 4 },
+                "list": [2, 5],
+                "keep": true,
+                "new": { "g": [6] },
+            })
+        );
+    }
+
+    #[test]
+    fn merge_by_index_with_tombstone() {
+        let options = MergeOptions::new()
+            .array_strategy(ArrayStrategy::MergeByIndex)
+            .tombstone(Tombstone::default());
+        assert_eq!(
+            json!({ "list": [1, 2, 3] }).merged_with::<Dfs>(
+                &json!({ "list": [{ "$delete": true }, 5, { "$delete": true }] }),
+                &options
+            ),
+            json!({ "list": [5] })
+        );
+        let base = json!({ "list": [{ "a": 1 }, { "b": 1, "z": 1 }, 3] });
+        let merge = json!({
+            "list": [
+                { "$delete": true },
+                { "b": 2, "z": { "$delete": true } },
+                4,
+                { "new": [{ "$delete": true }, 1] },
+            ],
+        });
+        assert_eq!(
+            base.merged_with::<Dfs>(&merge, &options),
+            json!({ "list": [{ "b": 2 }, 4, { "new": [1] }] })
+        );
+    }
+
+    #[test]
+    fn union_all_by_with_custom_tombstone() {
+        let tombstone = Tombstone::new(json!("__deleted__"));
+        let layers = [
+            json!({ "a": 1, "b": { "c": 2 }, "d": [1, 2] }),
+            json!({ "b": "__deleted__", "d": ["__deleted__"] }),
+            json!({ "a": "__deleted__", "e": "__deleted__" }),
+        ];
+        let mut union = tombstone.wrap(union_func);
+        assert_eq!(
+            Value::union_all_by::<Dfs, _, _>(&layers, &mut union),
+            json!({ "d": [2] })
+        );
+        assert_eq!(
+            json!(1).merged_by_recursive::<Dfs, _>(&json!("__deleted__"), &mut union),
+            Value::Null
+        );
+    }
+}
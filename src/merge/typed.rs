@@ -0,0 +1,186 @@
+use super::{Merge, MergeOptions};
+use crate::index::Path as IndexPath;
+use crate::iter::Traverser;
+use serde::{de::DeserializeOwned, Serialize};
+
+#[derive(Debug)]
+pub enum Error {
+    /// One of the values could not be serialized.
+    Serialize(serde_json::Error),
+    /// The merged value could not be deserialized at `path`.
+    Deserialize {
+        path: IndexPath,
+        source: serde_json::Error,
+    },
+}
+
+impl std::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serialize(err) => write!(f, "failed to serialize value: {err}"),
+            Self::Deserialize { path, source } => {
+                write!(f, "failed to deserialize merged value at {path}: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(err) | Self::Deserialize { source: err, .. } => Some(err),
+        }
+    }
+}
+
+/// Merges `overlay` into `base` by converting both to [`serde_json::Value`]s
+/// and merging them using [`Merge::merge_with`].
+///
+/// By default, fields that serialize to `null`, such as `None`, do not overwrite
+/// the base. See [`NullPolicy`](super::NullPolicy) for distinguishing absent fields
+/// from explicit `null` values.
+///
+/// # Errors
+///
+/// Returns an error if either value cannot be serialized,
+/// or if the merged value cannot be deserialized into `S`,
+/// e.g. because arrays were merged into a fixed size tuple.
+#[inline]
+pub fn merge_typed<T, S>(base: &S, overlay: &S, options: &MergeOptions) -> Result<S, Error>
+where
+    T: Traverser,
+    S: Serialize + DeserializeOwned,
+{
+    let mut merged = serde_json::to_value(base).map_err(Error::Serialize)?;
+    let overlay = serde_json::to_value(overlay).map_err(Error::Serialize)?;
+    merged.merge_with::<T>(&overlay, options);
+    serde_path_to_error::deserialize(merged).map_err(|err| {
+        let path = err
+            .path()
+            .iter()
+            .filter_map(|segment| match segment {
+                serde_path_to_error::Segment::Seq { index } => Some(IndexPath::new(*index)),
+                serde_path_to_error::Segment::Map { key } => Some(IndexPath::new(key.clone())),
+                serde_path_to_error::Segment::Enum { .. }
+                | serde_path_to_error::Segment::Unknown => None,
+            })
+            .fold(IndexPath::empty(), |path, segment| path.join(&segment));
+        Error::Deserialize {
+            path,
+            source: err.into_inner(),
+        }
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{merge_typed, Error};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use crate::merge::{ArrayStrategy, MergeOptions, NullPolicy};
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Server {
+        host: String,
+        port: Option<u16>,
+        tags: Vec<String>,
+        limits: BTreeMap<String, u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+        position: (i32, i32),
+    }
+
+    fn config(name: &str, port: Option<u16>, tag: &str) -> Config {
+        Config {
+            name: name.to_string(),
+            servers: vec![Server {
+                host: "localhost".to_string(),
+                port,
+                tags: vec![tag.to_string()],
+                limits: BTreeMap::from_iter([(tag.to_string(), 1)]),
+            }],
+            position: (0, 0),
+        }
+    }
+
+    #[test]
+    fn merge_typed_structs() {
+        let base = config("base", Some(80), "a");
+        let overlay = config("overlay", None, "b");
+        let options = MergeOptions::new()
+            .array_strategy(ArrayStrategy::MergeByIndex)
+            .array_strategy_at("/servers/*/tags", ArrayStrategy::Append)
+            .array_strategy_at("/position", ArrayStrategy::Replace);
+        let merged = merge_typed::<Dfs, _>(&base, &overlay, &options).unwrap();
+        assert_eq!(
+            merged,
+            Config {
+                name: "overlay".to_string(),
+                servers: vec![Server {
+                    host: "localhost".to_string(),
+                    port: Some(80),
+                    tags: vec!["a".to_string(), "b".to_string()],
+                    limits: BTreeMap::from_iter([("a".to_string(), 1), ("b".to_string(), 1)]),
+                }],
+                position: (0, 0),
+            }
+        );
+    }
+
+    #[test]
+    fn merge_typed_reports_path() {
+        let base = config("base", Some(80), "a");
+        let overlay = config("overlay", None, "b");
+        let err = merge_typed::<Dfs, _>(&base, &overlay, &MergeOptions::new()).unwrap_err();
+        let Error::Deserialize { path, .. } = &err else {
+            panic!("expected deserialize error, got {err:?}");
+        };
+        assert_eq!(path, &index!("position"));
+        assert!(err
+            .to_string()
+            .starts_with("failed to deserialize merged value at /position: "));
+    }
+
+    #[allow(clippy::option_option)]
+    #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+    struct Overlay {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<Option<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        port: Option<Option<u16>>,
+    }
+
+    #[test]
+    fn merge_typed_partial_overlay() {
+        let base = Overlay {
+            name: Some("app".to_string()),
+            description: Some(Some("an app".to_string())),
+            port: Some(Some(80)),
+        };
+        let overlay = Overlay {
+            name: None,
+            description: Some(None),
+            port: Some(Some(443)),
+        };
+        let options = MergeOptions::new().null_policy(NullPolicy::Clear);
+        assert_eq!(
+            merge_typed::<Dfs, _>(&base, &overlay, &options).unwrap(),
+            Overlay {
+                name: Some("app".to_string()),
+                description: None,
+                port: Some(Some(443)),
+            }
+        );
+    }
+}
@@ -1,8 +1,29 @@
-use super::Merge;
+use super::{Merge, MergeControl};
 use crate::index::Path as IndexPath;
 use crate::iter::Traverser;
 use serde_json::{Map, Value};
-use std::borrow::Borrow;
+use std::borrow::{Borrow, BorrowMut};
+use std::collections::BTreeMap;
+
+#[cfg(feature = "rayon")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator};
+
+/// Distinct scalar value supplied by some of the inputs of a union.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub value: Value,
+    /// Indices of the inputs that supplied the value.
+    pub sources: Vec<usize>,
+}
+
+/// Path where the inputs of a union disagreed on a scalar value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: IndexPath,
+    /// Distinct values in the order they were encountered.
+    /// The first candidate is the value kept in the union.
+    pub candidates: Vec<Candidate>,
+}
 
 pub trait Union: Sized {
     #[must_use]
@@ -18,6 +39,110 @@ pub trait Union: Sized {
         V: Borrow<Value>,
         F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> bool;
 
+    /// Unions all values like [`Union::union_all_by`], where `union` returns a
+    /// [`MergeControl`] to skip the children or siblings of a value or to stop
+    /// merging any further values.
+    #[inline]
+    #[must_use]
+    fn union_all_by_control<T, V, F>(values: impl IntoIterator<Item = V>, union: &mut F) -> Self
+    where
+        T: Traverser,
+        V: Borrow<Value>,
+        F: FnMut(&IndexPath, &mut Value, Option<&Value>) -> MergeControl,
+        Self: From<Value>,
+    {
+        let mut result = Value::Object(Map::default());
+        let mut stopped = false;
+        for v in values {
+            result.merge_by_recursive_control::<T, _>(v.borrow(), &mut |idx, this, other| {
+                let control = union(idx, this, other);
+                stopped |= control == MergeControl::Stop;
+                control
+            });
+            if stopped {
+                break;
+            }
+        }
+        Self::from(result)
+    }
+
+    /// Unions all values in parallel by reducing partial unions.
+    ///
+    /// The result is the same as for [`Union::union_all`] if the values at each
+    /// path are either all arrays, all objects or all neither.
+    /// Otherwise, it depends on how the values are split between threads,
+    /// because the union is not associative: the union of `[1]`, `"x"` and `[2]`
+    /// is `[1, 2]`, but the union of `[1]` with the union of `"x"` and `[2]` is `[1]`.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    #[must_use]
+    fn par_union_all<T, I, V>(values: I) -> Self
+    where
+        T: Traverser,
+        I: IntoParallelIterator<Item = V>,
+        I::Iter: IndexedParallelIterator,
+        V: Borrow<Value>,
+        Self: From<Value>,
+    {
+        Self::par_union_all_by::<T, I, V, _>(values, &union_func)
+    }
+
+    /// Unions all values in parallel by reducing partial unions.
+    ///
+    /// The result is the same as for [`Union::union_all_by`]
+    /// if `union` is associative.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    #[must_use]
+    fn par_union_all_by<T, I, V, F>(values: I, union: &F) -> Self
+    where
+        T: Traverser,
+        I: IntoParallelIterator<Item = V>,
+        I::Iter: IndexedParallelIterator,
+        V: Borrow<Value>,
+        F: Fn(&IndexPath, &mut Value, Option<&Value>) -> bool + Sync,
+        Self: From<Value>,
+    {
+        Self::from(super::par_reduce::<T, I, V, F>(values, union))
+    }
+
+    /// Unions all values like [`Union::union_all`] and reports the paths where
+    /// inputs supplied different non-null scalar values.
+    ///
+    /// Conflicts are ordered by their path.
+    #[inline]
+    #[must_use]
+    fn union_all_checked<T, V>(values: impl IntoIterator<Item = V>) -> (Self, Vec<Conflict>)
+    where
+        T: Traverser,
+        V: Borrow<Value>,
+        Self: From<Value>,
+    {
+        let mut result = Value::Object(Map::default());
+        let mut candidates: BTreeMap<IndexPath, Vec<Candidate>> = BTreeMap::new();
+        for (source, v) in values.into_iter().enumerate() {
+            result.merge_by_recursive::<T, _>(v.borrow(), &mut |idx, this, other| {
+                match other {
+                    // missing values are copied including their children
+                    Some(other) if this.is_null() => {
+                        add_candidates(&mut candidates, idx, other, source);
+                    }
+                    Some(other) if is_scalar(this) && is_scalar(other) => {
+                        add_candidates(&mut candidates, idx, other, source);
+                    }
+                    _ => {}
+                }
+                union_func(idx, this, other)
+            });
+        }
+        let conflicts = candidates
+            .into_iter()
+            .filter(|(_, candidates)| candidates.len() > 1)
+            .map(|(path, candidates)| Conflict { path, candidates })
+            .collect();
+        (Self::from(result), conflicts)
+    }
+
     fn union<T>(&mut self, other: &Self)
     where
         T: Traverser;
@@ -26,6 +151,26 @@ pub trait Union: Sized {
     where
         T: Traverser;
 
+    /// Unions `other` like [`Union::union_recursive`], but moves its values into
+    /// place instead of cloning them.
+    #[inline]
+    fn union_owned(&mut self, other: Self)
+    where
+        Self: BorrowMut<Value> + Into<Value>,
+    {
+        union_owned_func(self.borrow_mut(), other.into());
+    }
+
+    #[inline]
+    #[must_use]
+    fn into_union_owned(mut self, other: Self) -> Self
+    where
+        Self: BorrowMut<Value> + Into<Value>,
+    {
+        self.union_owned(other);
+        self
+    }
+
     #[inline]
     #[must_use]
     fn into_union<T>(mut self, other: &Self) -> Self
@@ -88,7 +233,63 @@ impl Union for Value {
     }
 }
 
-fn union_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool {
+/// Same as [`union_func`] applied recursively, but moving values out of `other`.
+fn union_owned_func(this: &mut Value, other: Value) {
+    match (this, other) {
+        (Value::Object(this), Value::Object(other)) => {
+            for (key, other) in other {
+                match this.get_mut(&key) {
+                    Some(this) => union_owned_func(this, other),
+                    None => {
+                        this.insert(key, other);
+                    }
+                }
+            }
+        }
+        (Value::Array(this), Value::Array(other)) => this.extend(other),
+        (this @ Value::Null, other) => *this = other,
+        _ => {}
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !(value.is_null() || value.is_object() || value.is_array())
+}
+
+/// Adds the scalar values of `value` and its nested objects as candidates.
+fn add_candidates(
+    candidates: &mut BTreeMap<IndexPath, Vec<Candidate>>,
+    idx: &IndexPath,
+    value: &Value,
+    source: usize,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let mut key_idx = idx.clone();
+                key_idx.add(key.clone());
+                add_candidates(candidates, &key_idx, value, source);
+            }
+        }
+        value if is_scalar(value) => {
+            let path_candidates = candidates.entry(idx.clone()).or_default();
+            match path_candidates.iter_mut().find(|c| &c.value == value) {
+                Some(candidate) => candidate.sources.push(source),
+                None => path_candidates.push(Candidate {
+                    value: value.clone(),
+                    sources: vec![source],
+                }),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The union function used by [`Union::union`] and [`Union::union_recursive`].
+///
+/// Objects are merged key by key, arrays are extended with other arrays
+/// and all remaining values are only replaced if they are `null`.
+pub fn union_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool {
     match (this, other) {
         // add new fields when merging two objects
         (&mut Value::Object(ref mut res), Some(Value::Object(other))) => {
@@ -116,6 +317,7 @@ fn union_func(_idx: &IndexPath, this: &mut Value, other: Option<&Value>) -> bool
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use crate::index;
     use crate::iter::dfs::Dfs;
     use itertools::Itertools;
     use pretty_assertions::assert_eq;
@@ -215,6 +417,179 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn union_all_checked_conflicts() {
+        let samples = [
+            json!({ "id": 1, "name": "a", "tags": ["x"], "meta": { "v": 1 } }),
+            json!({ "id": 1, "name": "b", "tags": ["y"], "meta": null }),
+            json!({ "id": 2, "name": null, "meta": { "v": 1, "w": true } }),
+            json!({ "id": 2, "name": "b", "meta": { "v": "1" } }),
+        ];
+        let (union, conflicts) = Value::union_all_checked::<Dfs, _>(&samples);
+        assert_eq!(&union, &Value::union_all::<Dfs, _>(&samples));
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    path: index!("id"),
+                    candidates: vec![
+                        Candidate {
+                            value: json!(1),
+                            sources: vec![0, 1],
+                        },
+                        Candidate {
+                            value: json!(2),
+                            sources: vec![2, 3],
+                        },
+                    ],
+                },
+                Conflict {
+                    path: index!("meta", "v"),
+                    candidates: vec![
+                        Candidate {
+                            value: json!(1),
+                            sources: vec![0, 2],
+                        },
+                        Candidate {
+                            value: json!("1"),
+                            sources: vec![3],
+                        },
+                    ],
+                },
+                Conflict {
+                    path: index!("name"),
+                    candidates: vec![
+                        Candidate {
+                            value: json!("a"),
+                            sources: vec![0],
+                        },
+                        Candidate {
+                            value: json!("b"),
+                            sources: vec![1, 3],
+                        },
+                    ],
+                },
+            ]
+        );
+
+        let (_, conflicts) =
+            Value::union_all_checked::<Dfs, _>([json!({ "a": 1 }), json!({ "a": 1 })]);
+        assert_eq!(conflicts, vec![]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_union_all_matches_union_all() {
+        let values: Vec<Value> = (0..1000)
+            .map(|i| {
+                json!({
+                    "id": i % 7,
+                    "name": if i % 3 == 0 { Value::Null } else { json!(format!("item {i}")) },
+                    "tags": [i % 5],
+                    "nested": { format!("key{}", i % 11): { "value": i } },
+                })
+            })
+            .collect();
+        assert_eq!(
+            Value::par_union_all::<Dfs, _, _>(&values),
+            Value::union_all::<Dfs, _>(&values)
+        );
+        assert_eq!(
+            Value::par_union_all::<Dfs, _, Value>(Vec::new()),
+            Value::union_all::<Dfs, Value>(Vec::new())
+        );
+    }
+
+    /// Maximum of unioned values, implementing only the required methods.
+    #[derive(Debug, PartialEq)]
+    struct Max(u64);
+
+    impl Union for Max {
+        fn union_all<T, V>(_iter: impl IntoIterator<Item = V>) -> Self {
+            Self(0)
+        }
+
+        fn union_all_by<T, V, F>(_iter: impl IntoIterator<Item = V>, _union: &mut F) -> Self {
+            Self(0)
+        }
+
+        fn union<T>(&mut self, other: &Self) {
+            self.0 = self.0.max(other.0);
+        }
+
+        fn union_recursive<T>(&mut self, other: &Self) {
+            self.0 = self.0.max(other.0);
+        }
+    }
+
+    #[test]
+    fn union_implementors() {
+        assert_eq!(Max(1).into_union::<Dfs>(&Max(2)), Max(2));
+        assert_eq!(Max(3).into_union_recursive::<Dfs>(&Max(2)), Max(3));
+    }
+
+    #[test]
+    fn union_is_not_associative() {
+        let union = |this: &Value, other: &Value| {
+            let mut this = this.clone();
+            this.merge_by_recursive::<Dfs, _>(other, &mut union_func);
+            this
+        };
+        let cases = [
+            (
+                [json!([1]), json!("x"), json!([2])],
+                json!([1, 2]),
+                json!([1]),
+            ),
+            (
+                [
+                    json!({"x": {"a": 1}}),
+                    json!({"x": 5}),
+                    json!({"x": {"b": 2}}),
+                ],
+                json!({"x": {"a": 1, "b": 2}}),
+                json!({"x": {"a": 1}}),
+            ),
+        ];
+        for ([a, b, c], sequential, tree) in cases {
+            assert_eq!(union(&union(&a, &b), &c), sequential);
+            assert_eq!(union(&a, &union(&b, &c)), tree);
+
+            let values = [json!({ "v": a }), json!({ "v": b }), json!({ "v": c })];
+            assert_eq!(
+                Value::union_all::<Dfs, _>(&values),
+                json!({ "v": sequential })
+            );
+            #[cfg(feature = "rayon")]
+            {
+                let unioned = Value::par_union_all::<Dfs, _, _>(&values);
+                assert!(unioned["v"] == sequential || unioned["v"] == tree);
+            }
+        }
+    }
+
+    #[test]
+    fn union_all_by_control_stop() {
+        let values = [
+            json!({ "a": 1, "b": { "c": 1 } }),
+            json!({ "d": 2, "stop": true, "e": 2 }),
+            json!({ "f": 3 }),
+        ];
+        let union: Value = Union::union_all_by_control::<Dfs, _, _>(
+            &values,
+            &mut |idx: &IndexPath, this: &mut Value, other: Option<&Value>| {
+                if idx.to_string() == "/stop" {
+                    return MergeControl::Stop;
+                }
+                union_func(idx, this, other).into()
+            },
+        );
+        assert_eq!(
+            union,
+            json!({ "a": 1, "b": { "c": 1 }, "d": 2, "stop": null, "e": null })
+        );
+    }
+
     #[test]
     fn union_recursive_complex() {
         let base = json!({
@@ -246,6 +621,7 @@ pub mod test {
             "cities": [ "london", "paris", "london" ],
             "new": [ "this is new" ],
         });
+        assert_eq!(&base.clone().into_union_owned(merge.clone()), &expected);
         assert_eq!(&base.into_union_recursive::<Dfs>(&merge), &expected);
     }
 }
@@ -0,0 +1,356 @@
+use super::Operation;
+use crate::index::{Index, Path as IndexPath};
+use crate::iter::{Iter, Traverser};
+use serde_json::Value;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    detect_moves: bool,
+    detect_copies: bool,
+}
+
+impl DiffOptions {
+    /// Combine removals and additions of equal object members into `move` operations.
+    #[inline]
+    #[must_use]
+    pub fn detect_moves(mut self, enabled: bool) -> Self {
+        self.detect_moves = enabled;
+        self
+    }
+
+    /// Emit `copy` operations for added objects and arrays
+    /// that are equal to an unchanged value.
+    #[inline]
+    #[must_use]
+    pub fn detect_copies(mut self, enabled: bool) -> Self {
+        self.detect_copies = enabled;
+        self
+    }
+}
+
+/// Computes the [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902)
+/// operations that transform `left` into `right`.
+///
+/// Only `add`, `remove`, and `replace` operations are emitted.
+/// Arrays are compared position-wise.
+#[inline]
+#[must_use]
+pub fn diff<T>(left: &Value, right: &Value) -> Vec<Operation>
+where
+    T: Traverser,
+{
+    diff_with::<T>(left, right, &DiffOptions::default())
+}
+
+/// Computes the operations that transform `left` into `right`,
+/// optionally detecting `move` and `copy` operations.
+#[must_use]
+pub fn diff_with<T>(left: &Value, right: &Value, options: &DiffOptions) -> Vec<Operation>
+where
+    T: Traverser,
+{
+    let mut ops = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut traverser = T::new();
+    traverser.set_limit(None);
+    traverser.set_depth(None);
+    while traverser
+        .process_next(right, |idx, new_value| {
+            let Some(new_value) = new_value else {
+                return false;
+            };
+            match (left.get_index(idx), new_value) {
+                (None, new_value) => {
+                    ops.push(Operation::Add {
+                        path: idx.clone(),
+                        value: new_value.clone(),
+                    });
+                    false
+                }
+                (Some(old_value), new_value) if old_value == new_value => {
+                    unchanged.push(idx.clone());
+                    false
+                }
+                (Some(Value::Object(old)), Value::Object(new)) => {
+                    ops.extend(old.keys().filter(|k| !new.contains_key(*k)).map(|k| {
+                        let mut path = idx.clone();
+                        path.add(k.clone());
+                        Operation::Remove { path }
+                    }));
+                    true
+                }
+                (Some(Value::Array(old)), Value::Array(new)) => {
+                    // remove from the back so that indices remain valid
+                    ops.extend((new.len()..old.len()).rev().map(|arr_idx| {
+                        let mut path = idx.clone();
+                        path.add(arr_idx);
+                        Operation::Remove { path }
+                    }));
+                    true
+                }
+                (Some(_), new_value) => {
+                    ops.push(Operation::Replace {
+                        path: idx.clone(),
+                        value: new_value.clone(),
+                    });
+                    false
+                }
+            }
+        })
+        .is_some()
+    {}
+
+    if options.detect_moves {
+        ops = detect_moves(left, ops);
+    }
+    if options.detect_copies {
+        detect_copies::<T>(left, &unchanged, &mut ops);
+    }
+    ops
+}
+
+fn detect_moves(left: &Value, ops: Vec<Operation>) -> Vec<Operation> {
+    // only object members are considered as move sources,
+    // as their paths remain valid when the removal is deferred
+    let mut removed: Vec<Option<(IndexPath, &Value)>> = ops
+        .iter()
+        .map(|op| match op {
+            Operation::Remove { path } if path.is_object_key() => {
+                left.get_index(path).map(|value| (path.clone(), value))
+            }
+            _ => None,
+        })
+        .collect();
+    let mut moved = vec![false; ops.len()];
+    let ops: Vec<Operation> = ops
+        .into_iter()
+        .map(|op| match op {
+            Operation::Add { path, value } => {
+                let source = removed.iter_mut().enumerate().find(|(_, removed)| {
+                    removed
+                        .as_ref()
+                        .is_some_and(|(_, removed_value)| *removed_value == &value)
+                });
+                match source {
+                    Some((remove_idx, removed)) => {
+                        let (from, _) = removed.take().unwrap();
+                        moved[remove_idx] = true;
+                        Operation::Move { from, path }
+                    }
+                    None => Operation::Add { path, value },
+                }
+            }
+            op => op,
+        })
+        .collect();
+    ops.into_iter()
+        .zip(moved)
+        .filter_map(|(op, moved)| (!moved).then_some(op))
+        .collect()
+}
+
+fn detect_copies<T>(left: &Value, unchanged: &[IndexPath], ops: &mut [Operation])
+where
+    T: Traverser,
+{
+    for op in ops.iter_mut() {
+        let Operation::Add { path, value } = op else {
+            continue;
+        };
+        let is_compound = match value {
+            Value::Object(map) => !map.is_empty(),
+            Value::Array(arr) => !arr.is_empty(),
+            _ => false,
+        };
+        if !is_compound {
+            continue;
+        }
+        let source = unchanged.iter().find_map(|root| {
+            let root_value = left.get_index(root)?;
+            root_value
+                .iter_recursive::<T>()
+                .find(|(_, candidate)| *candidate == &*value)
+                .map(|(idx, _)| root.clone().join(&idx))
+        });
+        if let Some(from) = source {
+            *op = Operation::Copy {
+                from,
+                path: path.clone(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{diff, diff_with, DiffOptions};
+    use crate::index;
+    use crate::iter::dfs::Dfs;
+    use crate::patch::{Operation, Patch};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    fn assert_round_trip(left: &Value, right: &Value, ops: &[Operation]) {
+        assert_eq!(&left.clone().patched(ops).unwrap(), right);
+    }
+
+    #[test]
+    fn diff_equal() {
+        let value = json!({"a": [1, 2, {"b": null}]});
+        assert_eq!(diff::<Dfs>(&value, &value), vec![]);
+    }
+
+    #[test]
+    fn diff_root() {
+        let left = json!({"a": 1});
+        let right = json!([1]);
+        let ops = diff::<Dfs>(&left, &right);
+        assert_eq!(
+            &ops,
+            &vec![Operation::Replace {
+                path: index!(),
+                value: json!([1])
+            }]
+        );
+        assert_round_trip(&left, &right, &ops);
+    }
+
+    #[test]
+    fn diff_object() {
+        let left = json!({
+            "title": "Goodbye!",
+            "author" : {
+                "givenName" : "John",
+                "familyName" : "Doe"
+            },
+            "content": "This will be unchanged"
+        });
+        let right = json!({
+            "title": "Hello!",
+            "author" : {
+                "givenName" : "John"
+            },
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-123-456-7890"
+        });
+        let ops = diff::<Dfs>(&left, &right);
+        assert_eq!(
+            &ops,
+            &vec![
+                Operation::Replace {
+                    path: index!("title"),
+                    value: json!("Hello!"),
+                },
+                Operation::Remove {
+                    path: index!("author", "familyName"),
+                },
+                Operation::Add {
+                    path: index!("phoneNumber"),
+                    value: json!("+01-123-456-7890"),
+                },
+            ]
+        );
+        assert_round_trip(&left, &right, &ops);
+    }
+
+    #[test]
+    fn diff_array() {
+        let left = json!({"a": [1, 2, 3, 4], "b": [1], "c": [{"d": 1}]});
+        let right = json!({"a": [1, 5], "b": [1, 2, 3], "c": [{"d": 2}]});
+        let ops = diff::<Dfs>(&left, &right);
+        assert_eq!(
+            &ops,
+            &vec![
+                Operation::Remove {
+                    path: index!("a", 3),
+                },
+                Operation::Remove {
+                    path: index!("a", 2),
+                },
+                Operation::Replace {
+                    path: index!("a", 1),
+                    value: json!(5),
+                },
+                Operation::Add {
+                    path: index!("b", 1),
+                    value: json!(2),
+                },
+                Operation::Add {
+                    path: index!("b", 2),
+                    value: json!(3),
+                },
+                Operation::Replace {
+                    path: index!("c", 0, "d"),
+                    value: json!(2),
+                },
+            ]
+        );
+        assert_round_trip(&left, &right, &ops);
+    }
+
+    #[test]
+    fn diff_detect_moves() {
+        let left = json!({"a": {"b": {"c": 1}}, "d": [1, 2]});
+        let right = json!({"a": {}, "e": {"c": 1}, "d": [1]});
+        let options = DiffOptions::default().detect_moves(true);
+        let ops = diff_with::<Dfs>(&left, &right, &options);
+        assert_eq!(
+            &ops,
+            &vec![
+                Operation::Move {
+                    from: index!("a", "b"),
+                    path: index!("e"),
+                },
+                Operation::Remove {
+                    path: index!("d", 1),
+                },
+            ]
+        );
+        assert_round_trip(&left, &right, &ops);
+    }
+
+    #[test]
+    fn diff_detect_copies() {
+        let left = json!({"a": {"b": [1, 2]}, "c": 1});
+        let right = json!({"a": {"b": [1, 2]}, "c": 1, "d": [1, 2], "e": 1});
+        let options = DiffOptions::default().detect_copies(true);
+        let ops = diff_with::<Dfs>(&left, &right, &options);
+        assert_eq!(
+            &ops,
+            &vec![
+                Operation::Copy {
+                    from: index!("a", "b"),
+                    path: index!("d"),
+                },
+                Operation::Add {
+                    path: index!("e"),
+                    value: json!(1),
+                },
+            ]
+        );
+        assert_round_trip(&left, &right, &ops);
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let values = [
+            json!(null),
+            json!({}),
+            json!([]),
+            json!({"a": [1, {"b": [true, false]}], "c": {"d": "e"}}),
+            json!({"a": [1], "c": {"d": "f", "g": null}, "h": 1}),
+            json!({"a": [{"b": [false]}, 1, 2], "c": "d"}),
+            json!([{"a": 1}, {"a": 2}, [3]]),
+            json!([{"a": 2}]),
+        ];
+        let options = DiffOptions::default()
+            .detect_moves(true)
+            .detect_copies(true);
+        for left in &values {
+            for right in &values {
+                assert_round_trip(left, right, &diff::<Dfs>(left, right));
+                assert_round_trip(left, right, &diff_with::<Dfs>(left, right, &options));
+            }
+        }
+    }
+}
@@ -0,0 +1,512 @@
+pub mod diff;
+
+use crate::index::{IndexRef, Path as IndexPath};
+use crate::pointer;
+use crate::utils::values_equal;
+pub use diff::{diff, diff_with, DiffOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902) JSON Patch operation.
+///
+/// Paths are (de)serialized as JSON pointers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Add {
+        #[serde(with = "pointer")]
+        path: IndexPath,
+        value: Value,
+    },
+    Remove {
+        #[serde(with = "pointer")]
+        path: IndexPath,
+    },
+    Replace {
+        #[serde(with = "pointer")]
+        path: IndexPath,
+        value: Value,
+    },
+    Move {
+        #[serde(with = "pointer")]
+        from: IndexPath,
+        #[serde(with = "pointer")]
+        path: IndexPath,
+    },
+    Copy {
+        #[serde(with = "pointer")]
+        from: IndexPath,
+        #[serde(with = "pointer")]
+        path: IndexPath,
+    },
+    Test {
+        #[serde(with = "pointer")]
+        path: IndexPath,
+        value: Value,
+    },
+}
+
+impl Operation {
+    /// Name of the operation as used in the `op` member.
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Remove { .. } => "remove",
+            Self::Replace { .. } => "replace",
+            Self::Move { .. } => "move",
+            Self::Copy { .. } => "copy",
+            Self::Test { .. } => "test",
+        }
+    }
+
+    /// Target path of the operation.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> &IndexPath {
+        match self {
+            Self::Add { path, .. }
+            | Self::Remove { path }
+            | Self::Replace { path, .. }
+            | Self::Move { path, .. }
+            | Self::Copy { path, .. }
+            | Self::Test { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The referenced value does not exist.
+    NotFound,
+    /// The parent of the referenced value is neither an object nor an array.
+    NotAContainer,
+    /// The array index is out of bounds or not a valid index.
+    InvalidArrayIndex,
+    /// A value cannot be moved into one of its own children.
+    MoveIntoChild,
+    /// The `test` operation failed.
+    TestFailed {
+        expected: Box<Value>,
+        actual: Box<Value>,
+    },
+}
+
+impl std::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "path does not exist"),
+            Self::NotAContainer => write!(f, "parent is neither an object nor an array"),
+            Self::InvalidArrayIndex => write!(f, "invalid array index"),
+            Self::MoveIntoChild => write!(f, "cannot move a value into one of its children"),
+            Self::TestFailed { expected, actual } => {
+                write!(f, "expected {expected} but found {actual}")
+            }
+        }
+    }
+}
+
+/// Error returned when applying a patch fails.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// Position of the failing operation in the patch.
+    pub index: usize,
+    /// Name of the failing operation.
+    pub op: &'static str,
+    /// Path at which the operation failed.
+    pub path: IndexPath,
+    pub kind: ErrorKind,
+}
+
+impl std::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "operation {} ({}) failed at {:?}: {}",
+            self.index,
+            self.op,
+            self.path.to_pointer(),
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub trait Patch: Sized {
+    /// Applies a [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902) JSON Patch.
+    ///
+    /// The patch is applied atomically:
+    /// if any operation fails, `self` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first operation that could not be applied.
+    fn apply_patch(&mut self, patch: &[Operation]) -> Result<(), Error>;
+
+    /// Applies a JSON Patch and returns the patched value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first operation that could not be applied.
+    #[inline]
+    fn patched(mut self, patch: &[Operation]) -> Result<Self, Error> {
+        self.apply_patch(patch)?;
+        Ok(self)
+    }
+}
+
+impl Patch for Value {
+    #[inline]
+    fn apply_patch(&mut self, patch: &[Operation]) -> Result<(), Error> {
+        // apply to a copy so that failing patches leave the value untouched
+        let mut patched = self.clone();
+        for (index, op) in patch.iter().enumerate() {
+            apply(&mut patched, op).map_err(|(path, kind)| Error {
+                index,
+                op: op.name(),
+                path: path.clone(),
+                kind,
+            })?;
+        }
+        *self = patched;
+        Ok(())
+    }
+}
+
+type OpError<'a> = (&'a IndexPath, ErrorKind);
+
+fn apply<'a>(value: &mut Value, op: &'a Operation) -> Result<(), OpError<'a>> {
+    match op {
+        Operation::Add { path, value: new } => add(value, path, new.clone()),
+        Operation::Remove { path } => remove(value, path).map(|_| ()),
+        Operation::Replace { path, value: new } => {
+            let target = get_mut(value, path).ok_or((path, ErrorKind::NotFound))?;
+            *target = new.clone();
+            Ok(())
+        }
+        Operation::Move { from, path } => {
+            if from == path {
+                return get(value, from)
+                    .map(|_| ())
+                    .ok_or((from, ErrorKind::NotFound));
+            }
+            if path.starts_with(from) {
+                return Err((path, ErrorKind::MoveIntoChild));
+            }
+            let moved = remove(value, from)?;
+            add(value, path, moved)
+        }
+        Operation::Copy { from, path } => {
+            let copied = get(value, from).ok_or((from, ErrorKind::NotFound))?.clone();
+            add(value, path, copied)
+        }
+        Operation::Test {
+            path,
+            value: expected,
+        } => match get(value, path) {
+            Some(actual) if values_equal(actual, expected) => Ok(()),
+            Some(actual) => Err((
+                path,
+                ErrorKind::TestFailed {
+                    expected: Box::new(expected.clone()),
+                    actual: Box::new(actual.clone()),
+                },
+            )),
+            None => Err((path, ErrorKind::NotFound)),
+        },
+    }
+}
+
+fn get<'v>(value: &'v Value, path: &[IndexRef]) -> Option<&'v Value> {
+    path.iter().try_fold(value, |value, index| match value {
+        Value::Object(map) => map.get(&pointer::object_key(index)),
+        Value::Array(arr) => pointer::array_index(index).and_then(|idx| arr.get(idx)),
+        _ => None,
+    })
+}
+
+fn get_mut<'v>(value: &'v mut Value, path: &[IndexRef]) -> Option<&'v mut Value> {
+    path.iter().try_fold(value, |value, index| match value {
+        Value::Object(map) => map.get_mut(&pointer::object_key(index)),
+        Value::Array(arr) => pointer::array_index(index).and_then(|idx| arr.get_mut(idx)),
+        _ => None,
+    })
+}
+
+fn add<'a>(value: &mut Value, path: &'a IndexPath, new: Value) -> Result<(), OpError<'a>> {
+    let Some((last, parent)) = path.split_last() else {
+        *value = new;
+        return Ok(());
+    };
+    match get_mut(value, parent) {
+        Some(Value::Object(map)) => {
+            map.insert(pointer::object_key(last), new);
+            Ok(())
+        }
+        Some(Value::Array(arr)) => {
+            if pointer::is_end_of_array(last) {
+                arr.push(new);
+                return Ok(());
+            }
+            match pointer::array_index(last) {
+                Some(idx) if idx <= arr.len() => {
+                    arr.insert(idx, new);
+                    Ok(())
+                }
+                _ => Err((path, ErrorKind::InvalidArrayIndex)),
+            }
+        }
+        Some(_) => Err((path, ErrorKind::NotAContainer)),
+        None => Err((path, ErrorKind::NotFound)),
+    }
+}
+
+fn remove<'a>(value: &mut Value, path: &'a IndexPath) -> Result<Value, OpError<'a>> {
+    let Some((last, parent)) = path.split_last() else {
+        return Ok(std::mem::take(value));
+    };
+    match get_mut(value, parent) {
+        Some(Value::Object(map)) => map
+            .shift_remove(&pointer::object_key(last))
+            .ok_or((path, ErrorKind::NotFound)),
+        Some(Value::Array(arr)) => match pointer::array_index(last) {
+            Some(idx) if idx < arr.len() => Ok(arr.remove(idx)),
+            _ => Err((path, ErrorKind::InvalidArrayIndex)),
+        },
+        Some(_) => Err((path, ErrorKind::NotAContainer)),
+        None => Err((path, ErrorKind::NotFound)),
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{Error, ErrorKind, Operation, Patch};
+    use crate::index;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    fn apply(value: Value, patch: Value) -> Result<Value, Error> {
+        let patch: Vec<Operation> = serde_json::from_value(patch).unwrap();
+        value.patched(&patch)
+    }
+
+    macro_rules! patch_tests {
+        ($($name:ident: $doc:tt, $patch:tt => $expected:tt,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(apply(json!($doc), json!($patch)), Ok(json!($expected)));
+                }
+            )*
+        }
+    }
+
+    // examples from RFC 6902 appendix A
+    patch_tests!(
+        rfc6902_add_object_member: {"foo": "bar"},
+            [{"op": "add", "path": "/baz", "value": "qux"}]
+            => {"baz": "qux", "foo": "bar"},
+        rfc6902_add_array_element: {"foo": ["bar", "baz"]},
+            [{"op": "add", "path": "/foo/1", "value": "qux"}]
+            => {"foo": ["bar", "qux", "baz"]},
+        rfc6902_remove_object_member: {"baz": "qux", "foo": "bar"},
+            [{"op": "remove", "path": "/baz"}]
+            => {"foo": "bar"},
+        rfc6902_remove_array_element: {"foo": ["bar", "qux", "baz"]},
+            [{"op": "remove", "path": "/foo/1"}]
+            => {"foo": ["bar", "baz"]},
+        rfc6902_replace_value: {"baz": "qux", "foo": "bar"},
+            [{"op": "replace", "path": "/baz", "value": "boo"}]
+            => {"baz": "boo", "foo": "bar"},
+        rfc6902_move_value: {"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}},
+            [{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]
+            => {"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}},
+        rfc6902_move_array_element: {"foo": ["all", "grass", "cows", "eat"]},
+            [{"op": "move", "from": "/foo/1", "path": "/foo/3"}]
+            => {"foo": ["all", "cows", "eat", "grass"]},
+        rfc6902_test_success: {"baz": "qux", "foo": ["a", 2, "c"]},
+            [
+                {"op": "test", "path": "/baz", "value": "qux"},
+                {"op": "test", "path": "/foo/1", "value": 2}
+            ]
+            => {"baz": "qux", "foo": ["a", 2, "c"]},
+        rfc6902_add_nested_member: {"foo": "bar"},
+            [{"op": "add", "path": "/child", "value": {"grandchild": {}}}]
+            => {"foo": "bar", "child": {"grandchild": {}}},
+        rfc6902_ignore_unrecognized_elements: {"foo": "bar"},
+            [{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]
+            => {"foo": "bar", "baz": "qux"},
+        rfc6902_escape_ordering: {"/": 9, "~1": 10},
+            [{"op": "test", "path": "/~01", "value": 10}]
+            => {"/": 9, "~1": 10},
+        rfc6902_add_array_value: {"foo": ["bar"]},
+            [{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]
+            => {"foo": ["bar", ["abc", "def"]]},
+        add_replaces_root: {"foo": "bar"},
+            [{"op": "add", "path": "", "value": [1]}]
+            => [1],
+        copy_value: {"foo": {"bar": [1]}},
+            [{"op": "copy", "from": "/foo/bar", "path": "/baz"}]
+            => {"foo": {"bar": [1]}, "baz": [1]},
+        move_to_same_path: {"foo": 1},
+            [{"op": "move", "from": "/foo", "path": "/foo"}]
+            => {"foo": 1},
+        array_index_into_object_key: {"0": {"1": "a"}},
+            [{"op": "replace", "path": "/0/1", "value": "b"}]
+            => {"0": {"1": "b"}},
+    );
+
+    #[test]
+    fn rfc6902_test_failure() {
+        let err = apply(
+            json!({"baz": "qux"}),
+            json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            Error {
+                index: 0,
+                op: "test",
+                path: index!("baz"),
+                kind: ErrorKind::TestFailed {
+                    expected: Box::new(json!("bar")),
+                    actual: Box::new(json!("qux"))
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_compares_numbers_by_value() {
+        let value = json!({"a": 1, "b": [{"c": 2.0}], "d": -3});
+        assert_eq!(
+            apply(
+                value.clone(),
+                json!([
+                    {"op": "test", "path": "/a", "value": 1.0},
+                    {"op": "test", "path": "/b", "value": [{"c": 2}]},
+                    {"op": "test", "path": "", "value": {"d": -3.0, "b": [{"c": 2}], "a": 1}},
+                ])
+            ),
+            Ok(value.clone())
+        );
+        assert!(apply(
+            value.clone(),
+            json!([{"op": "test", "path": "/a", "value": 1.5}])
+        )
+        .is_err());
+        assert!(apply(value, json!([{"op": "test", "path": "/a", "value": "1"}])).is_err());
+    }
+
+    #[test]
+    fn rfc6902_add_to_nonexistent_target() {
+        let err = apply(
+            json!({"foo": "bar"}),
+            json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::NotFound);
+        assert_eq!(err.path, index!("baz", "bat"));
+        assert_eq!(
+            err.to_string(),
+            r#"operation 0 (add) failed at "/baz/bat": path does not exist"#
+        );
+    }
+
+    #[test]
+    fn patch_errors() {
+        macro_rules! assert_error {
+            ($doc:tt, $patch:tt => $kind:expr) => {
+                assert_eq!(
+                    apply(json!($doc), json!($patch)).map_err(|err| err.kind),
+                    Err($kind)
+                );
+            };
+        }
+        assert_error!({"foo": [1]}, [{"op": "add", "path": "/foo/2", "value": 1}]
+            => ErrorKind::InvalidArrayIndex);
+        assert_error!({"foo": [1]}, [{"op": "add", "path": "/foo/01", "value": 1}]
+            => ErrorKind::InvalidArrayIndex);
+        assert_error!({"foo": [1]}, [{"op": "remove", "path": "/foo/1"}]
+            => ErrorKind::InvalidArrayIndex);
+        assert_error!({"foo": 1}, [{"op": "add", "path": "/foo/bar", "value": 1}]
+            => ErrorKind::NotAContainer);
+        assert_error!({"foo": 1}, [{"op": "remove", "path": "/bar"}]
+            => ErrorKind::NotFound);
+        assert_error!({"foo": 1}, [{"op": "remove", "path": "/foo/bar"}]
+            => ErrorKind::NotAContainer);
+        assert_error!({"foo": 1}, [{"op": "move", "from": "/foo/bar", "path": "/baz"}]
+            => ErrorKind::NotAContainer);
+        assert_error!({"foo": 1}, [{"op": "replace", "path": "/bar", "value": 1}]
+            => ErrorKind::NotFound);
+        assert_error!({"foo": {"bar": 1}}, [{"op": "move", "from": "/foo", "path": "/foo/bar"}]
+            => ErrorKind::MoveIntoChild);
+        assert_error!({"foo": 1}, [{"op": "copy", "from": "/bar", "path": "/baz"}]
+            => ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn patch_is_atomic() {
+        let mut value = json!({"foo": "bar", "list": [1, 2]});
+        let patch: Vec<Operation> = serde_json::from_value(json!([
+            {"op": "add", "path": "/baz", "value": "qux"},
+            {"op": "remove", "path": "/list/0"},
+            {"op": "test", "path": "/foo", "value": "not bar"},
+        ]))
+        .unwrap();
+        let err = value.apply_patch(&patch).unwrap_err();
+        assert_eq!(err.index, 2);
+        assert_eq!(err.op, "test");
+        assert_eq!(&value, &json!({"foo": "bar", "list": [1, 2]}));
+    }
+
+    #[test]
+    fn operation_serialization() {
+        let patch = json!([
+            {"op": "add", "path": "/a~1b/0", "value": {"c": 1}},
+            {"op": "remove", "path": "/m~0n"},
+            {"op": "replace", "path": "", "value": null},
+            {"op": "move", "from": "/a", "path": "/b"},
+            {"op": "copy", "from": "/a", "path": "/b/-"},
+            {"op": "test", "path": "/", "value": [1]},
+        ]);
+        let ops: Vec<Operation> = serde_json::from_value(patch.clone()).unwrap();
+        assert_eq!(
+            &ops,
+            &vec![
+                Operation::Add {
+                    path: index!("a/b", "0"),
+                    value: json!({"c": 1}),
+                },
+                Operation::Remove {
+                    path: index!("m~n"),
+                },
+                Operation::Replace {
+                    path: index!(),
+                    value: json!(null),
+                },
+                Operation::Move {
+                    from: index!("a"),
+                    path: index!("b"),
+                },
+                Operation::Copy {
+                    from: index!("a"),
+                    path: index!("b", "-"),
+                },
+                Operation::Test {
+                    path: index!(""),
+                    value: json!([1]),
+                },
+            ]
+        );
+        assert_eq!(&serde_json::to_value(&ops).unwrap(), &patch);
+        assert!(serde_json::from_value::<Operation>(json!({"op": "foo", "path": ""})).is_err());
+        assert!(serde_json::from_value::<Operation>(json!({"op": "add", "path": "a"})).is_err());
+        assert!(serde_json::from_value::<Operation>(json!({"op": "remove"})).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::index::{IndexRef, Kind as IndexKind, Path as IndexPath};
+#[cfg(feature = "patch")]
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_json::Value;
+
+/// Reference token referring to the (nonexistent) element after the last element
+/// of an array, which is used to append to arrays.
+pub const END_OF_ARRAY: &str = "-";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl std::fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid JSON pointer {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses an [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON pointer
+/// into an index path, unescaping `~1` to `/` and `~0` to `~`.
+///
+/// All reference tokens are parsed as object keys,
+/// as whether a token refers to an array element depends on the document.
+///
+/// # Errors
+///
+/// Returns an error if `pointer` is neither empty nor starts with `/`,
+/// or contains a `~` that is not followed by `0` or `1`.
+pub fn parse(pointer: &str) -> Result<IndexPath, ParseError> {
+    if pointer.is_empty() {
+        return Ok(IndexPath::empty());
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(ParseError(pointer.to_string()));
+    };
+    let mut path = IndexPath::empty();
+    for token in tokens.split('/') {
+        let mut key = String::with_capacity(token.len());
+        let mut chars = token.chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('~', Some('0')) => {
+                    key.push('~');
+                    chars.next();
+                }
+                ('~', Some('1')) => {
+                    key.push('/');
+                    chars.next();
+                }
+                ('~', _) => return Err(ParseError(pointer.to_string())),
+                (c, _) => key.push(c),
+            }
+        }
+        path.add(key);
+    }
+    Ok(path)
+}
+
+/// Formats an index path as a JSON pointer.
+#[must_use]
+pub fn format(path: &IndexPath) -> String {
+    path.iter().fold(String::new(), |mut pointer, index| {
+        pointer.push('/');
+        match index.kind() {
+            IndexKind::ObjectKey(key) => {
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+            IndexKind::ArrayIndex(idx) => pointer.push_str(&idx.to_string()),
+        }
+        pointer
+    })
+}
+
+/// Interprets `index` as an array index.
+///
+/// Object keys are accepted if they are valid array indices
+/// without leading zeros.
+#[must_use]
+pub fn array_index(index: &IndexRef) -> Option<usize> {
+    match index.kind() {
+        IndexKind::ArrayIndex(idx) => Some(*idx),
+        IndexKind::ObjectKey(key) => {
+            if key.is_empty()
+                || (key.len() > 1 && key.starts_with('0'))
+                || !key.bytes().all(|b| b.is_ascii_digit())
+            {
+                None
+            } else {
+                key.parse().ok()
+            }
+        }
+    }
+}
+
+/// Resolves the reference tokens of a parsed JSON pointer against `value`.
+///
+/// Tokens that refer to elements of arrays in `value` become array indices,
+/// where the `-` token refers to the index after the last element.
+/// All other tokens are kept as object keys, so that the resolved path
+/// can be used with [`Index::get_index`](crate::index::Index::get_index).
+#[must_use]
+pub fn resolve(value: &Value, path: &IndexPath) -> IndexPath {
+    let mut current = Some(value);
+    let mut resolved = IndexPath::empty();
+    for index in path {
+        if let Some(Value::Array(values)) = current {
+            let arr_idx = if is_end_of_array(index) {
+                Some(values.len())
+            } else {
+                array_index(index)
+            };
+            if let Some(arr_idx) = arr_idx {
+                resolved.add(arr_idx);
+                current = values.get(arr_idx);
+                continue;
+            }
+        }
+        let key = object_key(index);
+        current = current.and_then(|value| value.get(&key));
+        resolved.add(key);
+    }
+    resolved
+}
+
+/// Checks if `index` is the `-` token referring to the end of an array.
+#[must_use]
+pub fn is_end_of_array(index: &IndexRef) -> bool {
+    index.try_as_object_key() == Some(END_OF_ARRAY)
+}
+
+/// Interprets `index` as an object key.
+#[must_use]
+pub fn object_key(index: &IndexRef) -> String {
+    match index.kind() {
+        IndexKind::ObjectKey(key) => key.to_string(),
+        IndexKind::ArrayIndex(idx) => idx.to_string(),
+    }
+}
+
+#[cfg(feature = "patch")]
+pub(crate) fn serialize<S>(path: &IndexPath, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format(path))
+}
+
+#[cfg(feature = "patch")]
+pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<IndexPath, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pointer = String::deserialize(deserializer)?;
+    parse(&pointer).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{array_index, format, is_end_of_array, parse, resolve};
+    use crate::index;
+    use crate::index::Index;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn parse_pointer() {
+        assert_eq!(parse(""), Ok(index!()));
+        assert_eq!(parse("/"), Ok(index!("")));
+        assert_eq!(parse("/foo/0"), Ok(index!("foo", "0")));
+        assert_eq!(parse("/a~1b/m~0n"), Ok(index!("a/b", "m~n")));
+        assert_eq!(parse("/~01"), Ok(index!("~1")));
+        assert!(parse("foo").is_err());
+        assert!(parse("/~2").is_err());
+        assert!(parse("/~").is_err());
+        // examples from RFC 6901, section 5
+        assert_eq!(parse("/"), Ok(index!("")));
+        assert_eq!(parse("/a~1b"), Ok(index!("a/b")));
+        assert_eq!(parse("/c%d"), Ok(index!("c%d")));
+        assert_eq!(parse("/e^f"), Ok(index!("e^f")));
+        assert_eq!(parse("/g|h"), Ok(index!("g|h")));
+        assert_eq!(parse("/i\\j"), Ok(index!("i\\j")));
+        assert_eq!(parse("/k\"l"), Ok(index!("k\"l")));
+        assert_eq!(parse("/ "), Ok(index!(" ")));
+        assert_eq!(parse("/m~0n"), Ok(index!("m~n")));
+        assert_eq!(parse("/foo//bar/-"), Ok(index!("foo", "", "bar", "-")));
+    }
+
+    #[test]
+    fn resolve_pointer() {
+        let value = json!({ "list": [1, { "0": 2 }], "map": { "0": 3 } });
+        let resolved = |pointer| resolve(&value, &parse(pointer).unwrap());
+        assert_eq!(resolved("/list/1/0"), index!("list", 1, "0"));
+        assert_eq!(resolved("/map/0"), index!("map", "0"));
+        assert_eq!(resolved("/list/-"), index!("list", 2));
+        assert_eq!(resolved("/list/01"), index!("list", "01"));
+        assert_eq!(resolved("/missing/0"), index!("missing", "0"));
+        assert_eq!(value.get_index(resolved("/list/1/0")), Some(&json!(2)));
+        assert_eq!(value.get_index(resolved("/list/01")), None);
+    }
+
+    #[test]
+    fn format_pointer() {
+        assert_eq!(format(&index!()), "");
+        assert_eq!(format(&index!("")), "/");
+        assert_eq!(format(&index!("foo", 0)), "/foo/0");
+        assert_eq!(format(&index!("a/b", "m~n")), "/a~1b/m~0n");
+        assert_eq!(format(&index!("~1")), "/~01");
+    }
+
+    #[test]
+    fn pointer_array_index() {
+        assert_eq!(array_index(&index!("0")[0]), Some(0));
+        assert_eq!(array_index(&index!("12")[0]), Some(12));
+        assert_eq!(array_index(&index!(12)[0]), Some(12));
+        assert_eq!(array_index(&index!("01")[0]), None);
+        assert_eq!(array_index(&index!("-")[0]), None);
+        assert_eq!(array_index(&index!("")[0]), None);
+        assert_eq!(array_index(&index!("1a")[0]), None);
+        assert!(is_end_of_array(&index!("-")[0]));
+        assert!(!is_end_of_array(&index!("-1")[0]));
+    }
+}
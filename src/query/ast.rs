@@ -0,0 +1,139 @@
+use serde_json::Value;
+
+/// Segment of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    /// Selects children of the input nodes, e.g. `.a` or `[0, 1]`.
+    Child(Vec<Selector>),
+    /// Selects descendants of the input nodes, e.g. `..a` or `..[0, 1]`.
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(LogicalExpr),
+}
+
+/// Query within a filter, relative to the current node `@` or the root node `$`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilterQuery {
+    pub relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+impl FilterQuery {
+    /// Checks if the query selects at most one node.
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                matches!(
+                    selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Comparison(Comparable, ComparisonOp, Comparable),
+    Test(TestExpr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TestExpr {
+    /// Tests if the query selects any nodes.
+    Query(FilterQuery),
+    Function(FunctionExpr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ComparisonOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Comparable {
+    Literal(Value),
+    /// Singular query.
+    Query(FilterQuery),
+    Function(FunctionExpr),
+}
+
+/// Type of function parameters and results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FunctionType {
+    Value,
+    Logical,
+    Nodes,
+}
+
+/// Function extension defined by RFC 9535.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Self::Length),
+            "count" => Some(Self::Count),
+            "match" => Some(Self::Match),
+            "search" => Some(Self::Search),
+            "value" => Some(Self::Value),
+            _ => None,
+        }
+    }
+
+    pub fn parameters(self) -> &'static [FunctionType] {
+        match self {
+            Self::Length => &[FunctionType::Value],
+            Self::Count | Self::Value => &[FunctionType::Nodes],
+            Self::Match | Self::Search => &[FunctionType::Value, FunctionType::Value],
+        }
+    }
+
+    pub fn result(self) -> FunctionType {
+        match self {
+            Self::Length | Self::Count | Self::Value => FunctionType::Value,
+            Self::Match | Self::Search => FunctionType::Logical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FunctionExpr {
+    pub function: Function,
+    pub arguments: Vec<FunctionArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FunctionArgument {
+    Literal(Value),
+    Query(FilterQuery),
+    Logical(LogicalExpr),
+    Function(FunctionExpr),
+}
@@ -0,0 +1,336 @@
+use super::ast::{
+    Comparable, ComparisonOp, FilterQuery, Function, FunctionArgument, FunctionExpr, LogicalExpr,
+    Segment, Selector, TestExpr,
+};
+use super::iregexp;
+use crate::index::Path as IndexPath;
+use crate::utils::{compare_numbers, values_equal};
+use fancy_regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+pub(crate) type Node<'a> = (IndexPath, &'a Value);
+
+/// Applies `segments` to the nodes of `nodes`.
+pub(crate) fn query<'a>(
+    segments: &[Segment],
+    root: &'a Value,
+    nodes: Vec<Node<'a>>,
+) -> Vec<Node<'a>> {
+    segments.iter().fold(nodes, |nodes, segment| {
+        let mut selected = Vec::new();
+        for (path, value) in &nodes {
+            match segment {
+                Segment::Child(selectors) => {
+                    select_all(selectors, root, path, value, &mut selected);
+                }
+                Segment::Descendant(selectors) => {
+                    descend(selectors, root, path, value, &mut selected);
+                }
+            }
+        }
+        selected
+    })
+}
+
+fn child(path: &IndexPath, index: impl crate::index::JsonIndex + 'static) -> IndexPath {
+    let mut child = path.clone();
+    child.add(index);
+    child
+}
+
+/// Applies `selectors` to `value` and all of its descendants,
+/// visiting values before their descendants.
+fn descend<'a>(
+    selectors: &[Selector],
+    root: &'a Value,
+    path: &IndexPath,
+    value: &'a Value,
+    selected: &mut Vec<Node<'a>>,
+) {
+    select_all(selectors, root, path, value, selected);
+    match value {
+        Value::Array(values) => {
+            for (arr_idx, value) in values.iter().enumerate() {
+                descend(selectors, root, &child(path, arr_idx), value, selected);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                descend(selectors, root, &child(path, key.clone()), value, selected);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn select_all<'a>(
+    selectors: &[Selector],
+    root: &'a Value,
+    path: &IndexPath,
+    value: &'a Value,
+    selected: &mut Vec<Node<'a>>,
+) {
+    for selector in selectors {
+        select(selector, root, path, value, selected);
+    }
+}
+
+fn select<'a>(
+    selector: &Selector,
+    root: &'a Value,
+    path: &IndexPath,
+    value: &'a Value,
+    selected: &mut Vec<Node<'a>>,
+) {
+    match (selector, value) {
+        (Selector::Name(name), Value::Object(map)) => {
+            if let Some(value) = map.get(name) {
+                selected.push((child(path, name.clone()), value));
+            }
+        }
+        (Selector::Wildcard, Value::Object(map)) => {
+            selected.extend(
+                map.iter()
+                    .map(|(key, value)| (child(path, key.clone()), value)),
+            );
+        }
+        (Selector::Wildcard, Value::Array(values)) => {
+            selected.extend(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(arr_idx, value)| (child(path, arr_idx), value)),
+            );
+        }
+        (Selector::Index(index), Value::Array(values)) => {
+            if let Some(arr_idx) = normalize(*index, values.len()) {
+                if let Some(value) = values.get(arr_idx) {
+                    selected.push((child(path, arr_idx), value));
+                }
+            }
+        }
+        (Selector::Slice { start, end, step }, Value::Array(values)) => {
+            selected.extend(
+                slice(*start, *end, *step, values.len())
+                    .map(|arr_idx| (child(path, arr_idx), &values[arr_idx])),
+            );
+        }
+        (Selector::Filter(expr), Value::Object(map)) => {
+            selected.extend(
+                map.iter()
+                    .filter(|(_, value)| test(expr, root, value))
+                    .map(|(key, value)| (child(path, key.clone()), value)),
+            );
+        }
+        (Selector::Filter(expr), Value::Array(values)) => {
+            selected.extend(
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| test(expr, root, value))
+                    .map(|(arr_idx, value)| (child(path, arr_idx), value)),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Converts a possibly negative index into an index from the start of an array.
+fn normalize(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let index = if index < 0 { len + index } else { index };
+    usize::try_from(index).ok()
+}
+
+/// Returns the indices selected by a slice of an array of length `len`.
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn slice(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: usize,
+) -> impl Iterator<Item = usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |index: i64| if index < 0 { len + index } else { index };
+    let (mut index, bound) = match step.cmp(&0) {
+        Ordering::Greater => {
+            let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+            let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+            (lower, upper)
+        }
+        Ordering::Less => {
+            let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+            let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+            (upper, lower)
+        }
+        Ordering::Equal => (0, 0),
+    };
+    std::iter::from_fn(move || {
+        let selected = (step > 0 && index < bound) || (step < 0 && index > bound);
+        if !selected {
+            return None;
+        }
+        let arr_idx = usize::try_from(index).ok()?;
+        index += step;
+        Some(arr_idx)
+    })
+}
+
+/// Result of a function or comparable, which is `None` if it is nothing.
+type Evaluated<'a> = Option<Cow<'a, Value>>;
+
+fn test(expr: &LogicalExpr, root: &Value, current: &Value) -> bool {
+    match expr {
+        LogicalExpr::Or(operands) => operands.iter().any(|expr| test(expr, root, current)),
+        LogicalExpr::And(operands) => operands.iter().all(|expr| test(expr, root, current)),
+        LogicalExpr::Not(expr) => !test(expr, root, current),
+        LogicalExpr::Comparison(left, op, right) => {
+            let left = comparable(left, root, current);
+            let right = comparable(right, root, current);
+            compare(left.as_deref(), *op, right.as_deref())
+        }
+        LogicalExpr::Test(TestExpr::Query(query)) => !filter_query(query, root, current).is_empty(),
+        LogicalExpr::Test(TestExpr::Function(function)) => {
+            logical_function(function, root, current)
+        }
+    }
+}
+
+fn filter_query<'a>(query: &FilterQuery, root: &'a Value, current: &'a Value) -> Vec<Node<'a>> {
+    let start = if query.relative { current } else { root };
+    self::query(&query.segments, root, vec![(IndexPath::empty(), start)])
+}
+
+fn comparable<'a>(
+    comparable: &'a Comparable,
+    root: &'a Value,
+    current: &'a Value,
+) -> Evaluated<'a> {
+    match comparable {
+        Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+        Comparable::Query(query) => filter_query(query, root, current)
+            .into_iter()
+            .next()
+            .map(|(_, value)| Cow::Borrowed(value)),
+        Comparable::Function(function) => value_function(function, root, current),
+    }
+}
+
+fn compare(left: Option<&Value>, op: ComparisonOp, right: Option<&Value>) -> bool {
+    match op {
+        ComparisonOp::Eq => equal(left, right),
+        ComparisonOp::Ne => !equal(left, right),
+        ComparisonOp::Lt => less(left, right),
+        ComparisonOp::Le => less(left, right) || equal(left, right),
+        ComparisonOp::Gt => less(right, left),
+        ComparisonOp::Ge => less(right, left) || equal(left, right),
+    }
+}
+
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(left)), Some(Value::Number(right))) => {
+            compare_numbers(left, right) == Some(Ordering::Less)
+        }
+        (Some(Value::String(left)), Some(Value::String(right))) => left < right,
+        _ => false,
+    }
+}
+
+fn value_argument<'a>(
+    argument: &'a FunctionArgument,
+    root: &'a Value,
+    current: &'a Value,
+) -> Evaluated<'a> {
+    match argument {
+        FunctionArgument::Literal(value) => Some(Cow::Borrowed(value)),
+        FunctionArgument::Query(query) => filter_query(query, root, current)
+            .into_iter()
+            .next()
+            .map(|(_, value)| Cow::Borrowed(value)),
+        FunctionArgument::Function(function) => value_function(function, root, current),
+        // rejected by the parser
+        FunctionArgument::Logical(_) => None,
+    }
+}
+
+fn nodes_argument<'a>(
+    argument: &'a FunctionArgument,
+    root: &'a Value,
+    current: &'a Value,
+) -> Vec<Node<'a>> {
+    match argument {
+        FunctionArgument::Query(query) => filter_query(query, root, current),
+        // rejected by the parser
+        _ => Vec::new(),
+    }
+}
+
+fn value_function<'a>(
+    function: &'a FunctionExpr,
+    root: &'a Value,
+    current: &'a Value,
+) -> Evaluated<'a> {
+    let arguments = &function.arguments;
+    match function.function {
+        Function::Length => {
+            let length = match value_argument(&arguments[0], root, current)?.as_ref() {
+                Value::String(string) => string.chars().count(),
+                Value::Array(values) => values.len(),
+                Value::Object(map) => map.len(),
+                _ => return None,
+            };
+            Some(Cow::Owned(Value::from(length)))
+        }
+        Function::Count => Some(Cow::Owned(Value::from(
+            nodes_argument(&arguments[0], root, current).len(),
+        ))),
+        Function::Value => match nodes_argument(&arguments[0], root, current).as_slice() {
+            [(_, value)] => Some(Cow::Borrowed(*value)),
+            _ => None,
+        },
+        // rejected by the parser
+        Function::Match | Function::Search => None,
+    }
+}
+
+fn logical_function(function: &FunctionExpr, root: &Value, current: &Value) -> bool {
+    let arguments = &function.arguments;
+    match function.function {
+        Function::Match | Function::Search => {
+            let string = value_argument(&arguments[0], root, current);
+            let pattern = value_argument(&arguments[1], root, current);
+            let (Some(Value::String(string)), Some(Value::String(pattern))) =
+                (string.as_deref(), pattern.as_deref())
+            else {
+                return false;
+            };
+            let full = function.function == Function::Match;
+            regex(pattern, full).is_some_and(|regex| regex.is_match(string).unwrap_or(false))
+        }
+        // rejected by the parser
+        Function::Length | Function::Count | Function::Value => false,
+    }
+}
+
+/// Compiles an I-Regexp (RFC 9485) pattern, or returns `None` if it is invalid.
+fn regex(pattern: &str, full: bool) -> Option<Regex> {
+    let translated = iregexp::translate(pattern)?;
+    let translated = if full {
+        format!(r"\A(?:{translated})\z")
+    } else {
+        translated
+    };
+    Regex::new(&translated).ok()
+}
@@ -0,0 +1,293 @@
+//! Translation of [I-Regexp](https://datatracker.ietf.org/doc/html/rfc9485) patterns,
+//! the interoperable regular expressions used by the `match` and `search` functions.
+
+/// Translates an I-Regexp `pattern` into the syntax of [`fancy_regex`].
+///
+/// Returns `None` if `pattern` is not a valid I-Regexp, e.g. if it uses
+/// lookaround, backreferences or escapes like `\d` that other regular
+/// expression flavors support. Groups become non-capturing, `^` and `$`
+/// are matched literally and `.` does not match line breaks.
+pub(crate) fn translate(pattern: &str) -> Option<String> {
+    let mut translator = Translator {
+        chars: pattern.chars(),
+        translated: String::with_capacity(pattern.len() + 8),
+    };
+    translator.regexp()?;
+    if translator.chars.next().is_some() {
+        return None;
+    }
+    Some(translator.translated)
+}
+
+struct Translator<'a> {
+    chars: std::str::Chars<'a>,
+    translated: String,
+}
+
+impl Translator<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.chars.clone().nth(1)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        if matches {
+            self.chars.next();
+        }
+        matches
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.eat(expected).then_some(())
+    }
+
+    /// `i-regexp = branch *( "|" branch )`
+    fn regexp(&mut self) -> Option<()> {
+        self.branch()?;
+        while self.eat('|') {
+            self.translated.push('|');
+            self.branch()?;
+        }
+        Some(())
+    }
+
+    /// `branch = *piece`
+    fn branch(&mut self) -> Option<()> {
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            self.atom()?;
+            self.quantifier()?;
+        }
+        Some(())
+    }
+
+    fn quantifier(&mut self) -> Option<()> {
+        match self.peek() {
+            Some(c @ ('*' | '+' | '?')) => {
+                self.chars.next();
+                self.translated.push(c);
+            }
+            Some('{') => {
+                self.chars.next();
+                self.translated.push('{');
+                self.digits(1)?;
+                if self.eat(',') {
+                    self.translated.push(',');
+                    self.digits(0)?;
+                }
+                self.expect('}')?;
+                self.translated.push('}');
+            }
+            _ => {}
+        }
+        Some(())
+    }
+
+    fn digits(&mut self, min: usize) -> Option<()> {
+        let mut count = 0;
+        while let Some(digit) = self.peek().filter(char::is_ascii_digit) {
+            self.chars.next();
+            self.translated.push(digit);
+            count += 1;
+        }
+        (count >= min).then_some(())
+    }
+
+    fn atom(&mut self) -> Option<()> {
+        match self.chars.next()? {
+            '(' => {
+                self.translated.push_str("(?:");
+                self.regexp()?;
+                self.expect(')')?;
+                self.translated.push(')');
+            }
+            '.' => self.translated.push_str(r"[^\n\r]"),
+            '[' => self.class()?,
+            '\\' => self.escape()?,
+            c @ ('^' | '$') => {
+                self.translated.push('\\');
+                self.translated.push(c);
+            }
+            ')' | '*' | '+' | '?' | ']' | '{' | '|' | '}' => return None,
+            c => self.translated.push(c),
+        }
+        Some(())
+    }
+
+    /// Escape after a `\`, either `SingleCharEsc` or `charClassEsc`.
+    fn escape(&mut self) -> Option<()> {
+        match self.chars.next()? {
+            c @ ('(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | 'n' | 'r'
+            | 't' | '{' | '|' | '}') => {
+                self.translated.push('\\');
+                self.translated.push(c);
+            }
+            c @ ('p' | 'P') => self.category(c)?,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Unicode general category after `\p` or `\P`, e.g. `{Lu}`.
+    fn category(&mut self, escape: char) -> Option<()> {
+        self.expect('{')?;
+        let major = self.chars.next()?;
+        let minors = match major {
+            'L' => "lmotu",
+            'M' => "cen",
+            'N' => "dlo",
+            'P' => "cdefios",
+            'Z' => "lps",
+            'S' => "ckmo",
+            'C' => "cfno",
+            _ => return None,
+        };
+        let minor = self.peek().filter(|minor| minors.contains(*minor));
+        if minor.is_some() {
+            self.chars.next();
+        }
+        self.expect('}')?;
+        self.translated.push('\\');
+        self.translated.push(escape);
+        self.translated.push('{');
+        self.translated.push(major);
+        self.translated.extend(minor);
+        self.translated.push('}');
+        Some(())
+    }
+
+    /// `charClassExpr = "[" [ "^" ] ( "-" / CCE1 ) *CCE1 [ "-" ] "]"`, after the `[`.
+    fn class(&mut self) -> Option<()> {
+        self.translated.push('[');
+        if self.eat('^') {
+            self.translated.push('^');
+        }
+        if self.eat('-') {
+            self.translated.push_str(r"\-");
+        } else {
+            self.class_element()?;
+        }
+        loop {
+            match self.peek()? {
+                ']' => break,
+                '-' => {
+                    // a hyphen is only allowed in ranges or as the last character
+                    self.chars.next();
+                    if self.peek() != Some(']') {
+                        return None;
+                    }
+                    self.translated.push_str(r"\-");
+                }
+                _ => self.class_element()?,
+            }
+        }
+        self.expect(']')?;
+        self.translated.push(']');
+        Some(())
+    }
+
+    /// `CCE1 = ( CCchar [ "-" CCchar ] ) / charClassEsc`
+    fn class_element(&mut self) -> Option<()> {
+        if self.peek() == Some('\\') && matches!(self.peek_second(), Some('p' | 'P')) {
+            self.chars.next();
+            return self.escape();
+        }
+        self.class_char()?;
+        if self.peek() == Some('-') && self.peek_second() != Some(']') {
+            self.chars.next();
+            self.translated.push('-');
+            self.class_char()?;
+        }
+        Some(())
+    }
+
+    fn class_char(&mut self) -> Option<()> {
+        match self.chars.next()? {
+            '\\' => {
+                if matches!(self.peek(), Some('p' | 'P')) {
+                    return None;
+                }
+                self.escape()?;
+            }
+            '-' | '[' | ']' => return None,
+            // characters with a meaning in the character classes of the regex syntax
+            c @ ('&' | '~' | '^') => {
+                self.translated.push('\\');
+                self.translated.push(c);
+            }
+            c => self.translated.push(c),
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::translate;
+    use fancy_regex::Regex;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn iregexp_translate() {
+        assert_eq!(translate("a.b").as_deref(), Some(r"a[^\n\r]b"));
+        assert_eq!(translate("(ab)+|c{2,}").as_deref(), Some("(?:ab)+|c{2,}"));
+        assert_eq!(translate("^a$").as_deref(), Some(r"\^a\$"));
+        assert_eq!(translate(r"[^a-z\-&]").as_deref(), Some(r"[^a-z\-\&]"));
+        assert_eq!(translate(r"[-a-]").as_deref(), Some(r"[\-a\-]"));
+        assert_eq!(
+            translate(r"\p{Lu}\P{N}[\p{Zs}]").as_deref(),
+            Some(r"\p{Lu}\P{N}[\p{Zs}]")
+        );
+        assert_eq!(translate(r"\.\n\{").as_deref(), Some(r"\.\n\{"));
+        assert_eq!(translate("").as_deref(), Some(""));
+        for category in [
+            "L", "Ll", "Lm", "Lo", "Lt", "Lu", "M", "Mc", "Me", "Mn", "N", "Nd",
+        ]
+        .into_iter()
+        .chain([
+            "Nl", "No", "P", "Pc", "Pd", "Pe", "Pf", "Pi", "Po", "Ps", "Z", "Zl",
+        ])
+        .chain([
+            "Zp", "Zs", "S", "Sc", "Sk", "Sm", "So", "C", "Cc", "Cf", "Cn", "Co",
+        ]) {
+            let translated = translate(&format!(r"\p{{{category}}}")).unwrap();
+            assert!(Regex::new(&translated).is_ok(), "{category}");
+        }
+    }
+
+    #[test]
+    fn iregexp_invalid() {
+        for pattern in [
+            "(?=a)",
+            "(?<!a)b",
+            r"(a)\1",
+            r"\d",
+            r"\w+",
+            r"\b",
+            "a**",
+            "a*?",
+            "a{,2}",
+            "a{2",
+            "(a",
+            "a)",
+            "[]",
+            "[a",
+            "[a-b-c]",
+            "[a--]",
+            r"[\d]",
+            r"[a-\p{L}]",
+            r"\p{X}",
+            r"\p{Lx}",
+            r"\p{L",
+            "{",
+            "}",
+            "]",
+            "\\",
+        ] {
+            assert_eq!(translate(pattern), None, "{pattern:?}");
+        }
+    }
+}
@@ -0,0 +1,384 @@
+mod ast;
+mod eval;
+mod iregexp;
+mod parser;
+
+use crate::index::{Index, Kind as IndexKind, Path as IndexPath};
+use serde_json::Value;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset in the query at which parsing failed.
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid JSONPath query at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// [RFC 9535](https://datatracker.ietf.org/doc/html/rfc9535) JSONPath query.
+///
+/// Queries select nodes of a value as `(IndexPath, &Value)` pairs, in the order
+/// defined by RFC 9535. Object members are visited in the order of the object.
+/// The paths can be used with [`Index::get_index_mut`] or the merge APIs.
+///
+/// ```
+/// use serde_json::json;
+/// use serde_json_merge::index;
+/// use serde_json_merge::query::JsonPath;
+///
+/// let value = json!({
+///     "store": {
+///         "book": [
+///             { "author": "Nigel Rees", "price": 8.95 },
+///             { "author": "Evelyn Waugh", "price": 12.99 },
+///         ],
+///         "bicycle": { "color": "red", "price": 399 },
+///     }
+/// });
+/// let query = JsonPath::parse("$.store.book[?@.price < 10].author").unwrap();
+/// assert_eq!(
+///     query.query(&value),
+///     vec![(index!("store", "book", 0, "author"), &json!("Nigel Rees"))]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    query: String,
+    segments: Vec<ast::Segment>,
+}
+
+impl std::str::FromStr for JsonPath {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::parse(query)
+    }
+}
+
+impl std::fmt::Display for JsonPath {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.query)
+    }
+}
+
+impl JsonPath {
+    /// Parses a JSONPath query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `query` is not a valid and well-typed query.
+    #[inline]
+    pub fn parse(query: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            query: query.to_string(),
+            segments: parser::parse(query)?,
+        })
+    }
+
+    /// Returns the nodes of `value` selected by the query.
+    ///
+    /// Nodes may be selected more than once, e.g. by `$[0, 0]`.
+    #[inline]
+    #[must_use]
+    pub fn query<'a>(&self, value: &'a Value) -> Vec<(IndexPath, &'a Value)> {
+        eval::query(&self.segments, value, vec![(IndexPath::empty(), value)])
+    }
+
+    /// Returns the paths of the nodes of `value` selected by the query.
+    #[inline]
+    #[must_use]
+    pub fn query_paths(&self, value: &Value) -> Vec<IndexPath> {
+        self.query(value)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Calls `func` for each node of `value` selected by the query.
+    ///
+    /// The nodes are selected before any of them are mutated. Nodes that
+    /// no longer exist when it is their turn, e.g. because a parent node
+    /// was replaced, are skipped.
+    #[inline]
+    pub fn query_mut(&self, value: &mut Value, mut func: impl FnMut(&IndexPath, &mut Value)) {
+        for path in self.query_paths(value) {
+            if let Some(node) = value.get_index_mut(&path) {
+                func(&path, node);
+            }
+        }
+    }
+}
+
+/// Queries values using [`JsonPath`] queries.
+pub trait Query {
+    /// Returns the nodes selected by `query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `query` is not a valid JSONPath query.
+    fn query(&self, query: &str) -> Result<Vec<(IndexPath, &Value)>, ParseError>;
+
+    /// Calls `func` for each node selected by `query`, see [`JsonPath::query_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `query` is not a valid JSONPath query.
+    fn query_mut(
+        &mut self,
+        query: &str,
+        func: impl FnMut(&IndexPath, &mut Value),
+    ) -> Result<(), ParseError>;
+}
+
+impl Query for Value {
+    #[inline]
+    fn query(&self, query: &str) -> Result<Vec<(IndexPath, &Value)>, ParseError> {
+        Ok(JsonPath::parse(query)?.query(self))
+    }
+
+    #[inline]
+    fn query_mut(
+        &mut self,
+        query: &str,
+        func: impl FnMut(&IndexPath, &mut Value),
+    ) -> Result<(), ParseError> {
+        JsonPath::parse(query)?.query_mut(self, func);
+        Ok(())
+    }
+}
+
+/// Formats `path` as a normalized path, e.g. `$['store']['book'][0]`.
+///
+/// See [RFC 9535, section 2.7](https://datatracker.ietf.org/doc/html/rfc9535#section-2.7).
+#[must_use]
+pub fn normalized_path(path: &IndexPath) -> String {
+    let mut normalized = String::from("$");
+    for index in path {
+        match index.kind() {
+            IndexKind::ObjectKey(key) => {
+                normalized.push_str("['");
+                for c in key.chars() {
+                    match c {
+                        '\u{8}' => normalized.push_str(r"\b"),
+                        '\u{c}' => normalized.push_str(r"\f"),
+                        '\n' => normalized.push_str(r"\n"),
+                        '\r' => normalized.push_str(r"\r"),
+                        '\t' => normalized.push_str(r"\t"),
+                        '\'' => normalized.push_str(r"\'"),
+                        '\\' => normalized.push_str(r"\\"),
+                        c if c < '\u{20}' => {
+                            let _ = write!(normalized, r"\u{:04x}", u32::from(c));
+                        }
+                        c => normalized.push(c),
+                    }
+                }
+                normalized.push_str("']");
+            }
+            IndexKind::ArrayIndex(arr_idx) => {
+                let _ = write!(normalized, "[{arr_idx}]");
+            }
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{normalized_path, JsonPath, Query};
+    use crate::index;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use std::path::Path;
+
+    /// Runs a test case in the format of the JSONPath compliance test suite.
+    fn run_test_case(test: &Value) {
+        let name = &test["name"];
+        let selector = test["selector"].as_str().unwrap();
+        let query = JsonPath::parse(selector);
+        if test["invalid_selector"] == json!(true) {
+            assert!(query.is_err(), "{name}: {selector:?} should be invalid");
+            return;
+        }
+        let query = query.unwrap_or_else(|err| panic!("{name}: {selector:?}: {err}"));
+        let nodes = query.query(&test["document"]);
+        let values: Value = nodes.iter().map(|(_, value)| (*value).clone()).collect();
+        let paths: Value = nodes
+            .iter()
+            .map(|(path, _)| Value::String(normalized_path(path)))
+            .collect();
+        if let Some(result) = test.get("result") {
+            assert_eq!(&values, result, "{name}: {selector:?}");
+        }
+        if let Some(Value::Array(results)) = test.get("results") {
+            assert!(results.contains(&values), "{name}: {selector:?}: {values}");
+        }
+        if let Some(result_paths) = test.get("result_paths") {
+            assert_eq!(&paths, result_paths, "{name}: {selector:?}");
+        }
+        if let Some(Value::Array(results_paths)) = test.get("results_paths") {
+            assert!(
+                results_paths.contains(&paths),
+                "{name}: {selector:?}: {paths}"
+            );
+        }
+    }
+
+    /// Runs all test suites in `tests/fixtures/jsonpath`, including the
+    /// [compliance test suite](https://github.com/jsonpath-standard/jsonpath-compliance-test-suite)
+    /// when its `cts.json` is placed there.
+    #[test]
+    fn query_compliance() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/jsonpath");
+        let mut suites: Vec<_> = std::fs::read_dir(&fixtures)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        suites.sort();
+        assert!(!suites.is_empty());
+        for suite in suites {
+            let suite: Value =
+                serde_json::from_str(&std::fs::read_to_string(&suite).unwrap()).unwrap();
+            for test in suite["tests"].as_array().unwrap() {
+                run_test_case(test);
+            }
+        }
+    }
+
+    #[cfg(feature = "merge")]
+    #[test]
+    fn query_paths_can_be_used_for_merging() {
+        use crate::{Dfs, Merge};
+
+        let mut value = json!({
+            "services": [
+                { "name": "web", "port": 80 },
+                { "name": "db", "port": 5432, "internal": true },
+            ],
+        });
+        let query = JsonPath::parse("$.services[?@.internal]").unwrap();
+        for path in query.query_paths(&value) {
+            value
+                .merge_index::<Dfs>(&path, &json!({ "port": 5433, "replicas": 2 }))
+                .unwrap();
+        }
+        assert_eq!(
+            value["services"][1],
+            json!({ "name": "db", "port": 5433, "internal": true, "replicas": 2 })
+        );
+    }
+
+    #[test]
+    fn query_mut() {
+        let mut value = json!({
+            "a": [1, 2, { "b": 3 }],
+            "c": { "b": 4 },
+        });
+        let mut paths = Vec::new();
+        value
+            .query_mut("$..b", |path, value| {
+                paths.push(path.clone());
+                *value = json!(value.as_i64().unwrap() * 10);
+            })
+            .unwrap();
+        assert_eq!(paths, vec![index!("a", 2, "b"), index!("c", "b")]);
+        assert_eq!(value, json!({ "a": [1, 2, { "b": 30 }], "c": { "b": 40 } }));
+
+        // nodes removed by an earlier callback are skipped
+        let mut visited = Vec::new();
+        JsonPath::parse("$..*")
+            .unwrap()
+            .query_mut(&mut value, |path, value| {
+                visited.push(path.clone());
+                if value.is_object() {
+                    *value = Value::Null;
+                }
+            });
+        assert_eq!(
+            visited,
+            vec![
+                index!("a"),
+                index!("c"),
+                index!("a", 0),
+                index!("a", 1),
+                index!("a", 2),
+            ]
+        );
+        assert_eq!(value, json!({ "a": [1, 2, null], "c": null }));
+        assert!(value.query("$[").is_err());
+    }
+
+    #[test]
+    fn query_regex_functions() {
+        let value = json!(["ab", "aab", "a\nb", "^a$", "1"]);
+        let selected = |query: &str| -> Vec<Value> {
+            value
+                .query(query)
+                .unwrap()
+                .into_iter()
+                .map(|(_, value)| value.clone())
+                .collect()
+        };
+        assert_eq!(selected("$[?match(@, 'a.b')]"), vec![json!("aab")]);
+        assert_eq!(
+            selected("$[?search(@, 'a.?b')]"),
+            vec![json!("ab"), json!("aab")]
+        );
+        // `^` and `$` are not anchors
+        assert_eq!(selected("$[?match(@, '^a$')]"), vec![json!("^a$")]);
+        // invalid I-Regexp patterns never match
+        for pattern in [r"\\d", "(?=a)a", r"(a)\\1", "a*?"] {
+            assert_eq!(
+                selected(&format!("$[?search(@, '{pattern}')]")),
+                Vec::<Value>::new(),
+                "{pattern}"
+            );
+            assert_eq!(
+                selected(&format!("$[?!search(@, '{pattern}')]")).len(),
+                5,
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn query_parse_errors() {
+        let err = JsonPath::parse("$.a[?@.b = 1]").unwrap_err();
+        assert_eq!(err.position, 9);
+        assert_eq!(
+            err.to_string(),
+            "invalid JSONPath query at position 9: expected `,` or `]`"
+        );
+        assert_eq!(
+            "$..book[?@.price<10]"
+                .parse::<JsonPath>()
+                .unwrap()
+                .to_string(),
+            "$..book[?@.price<10]"
+        );
+    }
+
+    #[test]
+    fn query_normalized_path() {
+        assert_eq!(normalized_path(&index!()), "$");
+        assert_eq!(
+            normalized_path(&index!("store", "book", 0, "it's\\")),
+            r"$['store']['book'][0]['it\'s\\']"
+        );
+        assert_eq!(normalized_path(&index!("\u{b}\n")), r"$['\u000b\n']");
+    }
+}
@@ -0,0 +1,533 @@
+use super::ast::{
+    Comparable, ComparisonOp, FilterQuery, Function, FunctionArgument, FunctionExpr, FunctionType,
+    LogicalExpr, Segment, Selector, TestExpr,
+};
+use super::ParseError;
+use serde_json::Value;
+
+/// Largest integer that can be represented exactly in I-JSON.
+const MAX_INT: i64 = (1 << 53) - 1;
+
+/// Parses a JSONPath query into its segments.
+pub(crate) fn parse(query: &str) -> Result<Vec<Segment>, ParseError> {
+    let mut parser = Parser {
+        input: query,
+        pos: 0,
+    };
+    if !parser.eat('$') {
+        return parser.error("expected root identifier `$`");
+    }
+    let segments = parser.segments()?;
+    if parser.pos < query.len() {
+        return parser.error("unexpected character");
+    }
+    Ok(segments)
+}
+
+/// Primary expression, which is either a comparable or a test expression.
+enum Primary {
+    Literal(Value),
+    Query(FilterQuery),
+    Function(FunctionExpr),
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.pos,
+            message: message.to_string(),
+        })
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.eat_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(&format!("expected `{c}`"))
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, ParseError> {
+        let mut segments = Vec::new();
+        loop {
+            let start = self.pos;
+            self.skip_blank();
+            if !matches!(self.peek(), Some('.' | '[')) {
+                // blanks are only allowed before segments
+                self.pos = start;
+                return Ok(segments);
+            }
+            segments.push(self.segment()?);
+        }
+    }
+
+    fn segment(&mut self) -> Result<Segment, ParseError> {
+        if self.eat_str("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.bracketed_selection()?,
+                Some('*') => {
+                    self.bump();
+                    vec![Selector::Wildcard]
+                }
+                Some(c) if is_name_first(c) => vec![Selector::Name(self.member_name())],
+                _ => return self.error("expected selector after `..`"),
+            };
+            return Ok(Segment::Descendant(selectors));
+        }
+        if self.eat('.') {
+            let selector = match self.peek() {
+                Some('*') => {
+                    self.bump();
+                    Selector::Wildcard
+                }
+                Some(c) if is_name_first(c) => Selector::Name(self.member_name()),
+                _ => return self.error("expected member name or `*` after `.`"),
+            };
+            return Ok(Segment::Child(vec![selector]));
+        }
+        Ok(Segment::Child(self.bracketed_selection()?))
+    }
+
+    fn member_name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.bump();
+        }
+        self.input[start..self.pos].to_string()
+    }
+
+    fn bracketed_selection(&mut self) -> Result<Vec<Selector>, ParseError> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            if !self.eat(',') {
+                return self.error("expected `,` or `]`");
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, ParseError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string_literal()?)),
+            Some('*') => {
+                self.bump();
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.bump();
+                self.skip_blank();
+                Ok(Selector::Filter(self.logical_or()?))
+            }
+            _ => {
+                let start = self.int()?;
+                self.skip_blank();
+                if !self.eat(':') {
+                    return match start {
+                        Some(index) => Ok(Selector::Index(index)),
+                        None => self.error("expected selector"),
+                    };
+                }
+                self.skip_blank();
+                let end = self.int()?;
+                self.skip_blank();
+                let mut step = None;
+                if self.eat(':') {
+                    self.skip_blank();
+                    step = self.int()?;
+                }
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    /// Parses an optional integer.
+    fn int(&mut self) -> Result<Option<i64>, ParseError> {
+        let start = self.pos;
+        let negative = self.eat('-');
+        let digits = self.digits();
+        match digits {
+            "" if negative => self.error("expected digits"),
+            "" => Ok(None),
+            "0" if negative => self.error("negative zero is not a valid integer"),
+            digits if digits.len() > 1 && digits.starts_with('0') => {
+                self.error("leading zeros are not allowed")
+            }
+            _ => match self.input[start..self.pos].parse::<i64>() {
+                Ok(int) if (-MAX_INT..=MAX_INT).contains(&int) => Ok(Some(int)),
+                _ => self.error("integer is out of range"),
+            },
+        }
+    }
+
+    fn digits(&mut self) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn string_literal(&mut self) -> Result<String, ParseError> {
+        let Some(quote) = self.bump() else {
+            return self.error("expected string literal");
+        };
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return self.error("unterminated string literal"),
+                Some(c) if c == quote => return Ok(string),
+                Some('\\') => string.push(self.escape(quote)?),
+                Some(c) if c < '\u{20}' => {
+                    return self.error("control characters must be escaped");
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self, quote: char) -> Result<char, ParseError> {
+        let c = match self.bump() {
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some(c @ ('/' | '\\')) => c,
+            Some(c) if c == quote => c,
+            Some('u') => {
+                let high = self.hex4()?;
+                if (0xDC00..0xE000).contains(&high) {
+                    return self.error("unpaired low surrogate");
+                }
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high)
+                        .map_or_else(|| self.error("invalid unicode escape"), Ok);
+                }
+                if !self.eat_str("\\u") {
+                    return self.error("unpaired high surrogate");
+                }
+                let low = self.hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return self.error("invalid low surrogate");
+                }
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return char::from_u32(code)
+                    .map_or_else(|| self.error("invalid unicode escape"), Ok);
+            }
+            _ => return self.error("invalid escape sequence"),
+        };
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let hex = self.rest().get(..4).unwrap_or_default();
+        if hex.len() != 4 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return self.error("expected four hexadecimal digits");
+        }
+        let code = u32::from_str_radix(hex, 16).unwrap_or_default();
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn logical_or(&mut self) -> Result<LogicalExpr, ParseError> {
+        let mut operands = vec![self.logical_and()?];
+        loop {
+            let start = self.pos;
+            self.skip_blank();
+            if !self.eat_str("||") {
+                self.pos = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.logical_and()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.remove(0)
+        } else {
+            LogicalExpr::Or(operands)
+        })
+    }
+
+    fn logical_and(&mut self) -> Result<LogicalExpr, ParseError> {
+        let mut operands = vec![self.basic_expr()?];
+        loop {
+            let start = self.pos;
+            self.skip_blank();
+            if !self.eat_str("&&") {
+                self.pos = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.basic_expr()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.remove(0)
+        } else {
+            LogicalExpr::And(operands)
+        })
+    }
+
+    fn basic_expr(&mut self) -> Result<LogicalExpr, ParseError> {
+        if self.eat('!') {
+            self.skip_blank();
+            let expr = if self.peek() == Some('(') {
+                self.paren_expr()?
+            } else {
+                let primary = self.primary()?;
+                self.test_expr(primary)?
+            };
+            return Ok(LogicalExpr::Not(Box::new(expr)));
+        }
+        if self.peek() == Some('(') {
+            return self.paren_expr();
+        }
+        let primary = self.primary()?;
+        let start = self.pos;
+        self.skip_blank();
+        let Some(op) = self.comparison_op() else {
+            self.pos = start;
+            return self.test_expr(primary);
+        };
+        let left = self.comparable(primary)?;
+        self.skip_blank();
+        let right = self.primary()?;
+        let right = self.comparable(right)?;
+        Ok(LogicalExpr::Comparison(left, op, right))
+    }
+
+    fn paren_expr(&mut self) -> Result<LogicalExpr, ParseError> {
+        self.expect('(')?;
+        self.skip_blank();
+        let expr = self.logical_or()?;
+        self.skip_blank();
+        self.expect(')')?;
+        Ok(expr)
+    }
+
+    fn comparison_op(&mut self) -> Option<ComparisonOp> {
+        let ops = [
+            ("==", ComparisonOp::Eq),
+            ("!=", ComparisonOp::Ne),
+            ("<=", ComparisonOp::Le),
+            (">=", ComparisonOp::Ge),
+            ("<", ComparisonOp::Lt),
+            (">", ComparisonOp::Gt),
+        ];
+        ops.into_iter()
+            .find_map(|(token, op)| self.eat_str(token).then_some(op))
+    }
+
+    fn test_expr(&self, primary: Primary) -> Result<LogicalExpr, ParseError> {
+        match primary {
+            Primary::Query(query) => Ok(LogicalExpr::Test(TestExpr::Query(query))),
+            Primary::Function(function) if function.function.result() != FunctionType::Value => {
+                Ok(LogicalExpr::Test(TestExpr::Function(function)))
+            }
+            Primary::Function(_) => self.error("function result must be compared"),
+            Primary::Literal(_) => self.error("literal must be compared"),
+        }
+    }
+
+    fn comparable(&self, primary: Primary) -> Result<Comparable, ParseError> {
+        match primary {
+            Primary::Literal(value) => Ok(Comparable::Literal(value)),
+            Primary::Query(query) if query.is_singular() => Ok(Comparable::Query(query)),
+            Primary::Query(_) => self.error("only singular queries can be compared"),
+            Primary::Function(function) if function.function.result() == FunctionType::Value => {
+                Ok(Comparable::Function(function))
+            }
+            Primary::Function(_) => self.error("function result cannot be compared"),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Primary, ParseError> {
+        match self.peek() {
+            Some('@' | '$') => Ok(Primary::Query(self.filter_query()?)),
+            Some('\'' | '"') => Ok(Primary::Literal(Value::String(self.string_literal()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Primary::Literal(self.number()?)),
+            Some(c) if c.is_ascii_lowercase() => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                {
+                    self.pos += 1;
+                }
+                let name = &self.input[start..self.pos];
+                if self.peek() == Some('(') {
+                    let Some(function) = Function::from_name(name) else {
+                        self.pos = start;
+                        return self.error("unknown function");
+                    };
+                    return Ok(Primary::Function(self.function_expr(function)?));
+                }
+                match name {
+                    "true" => Ok(Primary::Literal(Value::Bool(true))),
+                    "false" => Ok(Primary::Literal(Value::Bool(false))),
+                    "null" => Ok(Primary::Literal(Value::Null)),
+                    _ => {
+                        self.pos = start;
+                        self.error("expected literal or function")
+                    }
+                }
+            }
+            _ => self.error("expected expression"),
+        }
+    }
+
+    fn filter_query(&mut self) -> Result<FilterQuery, ParseError> {
+        let relative = match self.bump() {
+            Some('@') => true,
+            Some('$') => false,
+            _ => return self.error("expected `@` or `$`"),
+        };
+        Ok(FilterQuery {
+            relative,
+            segments: self.segments()?,
+        })
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        self.eat('-');
+        match self.digits() {
+            "" => return self.error("expected digits"),
+            digits if digits.len() > 1 && digits.starts_with('0') => {
+                return self.error("leading zeros are not allowed");
+            }
+            _ => {}
+        }
+        if self.eat('.') && self.digits().is_empty() {
+            return self.error("expected fraction digits");
+        }
+        if self.eat('e') || self.eat('E') {
+            let _ = self.eat('-') || self.eat('+');
+            if self.digits().is_empty() {
+                return self.error("expected exponent digits");
+            }
+        }
+        serde_json::from_str(&self.input[start..self.pos])
+            .map_or_else(|_| self.error("invalid number"), Ok)
+    }
+
+    fn function_expr(&mut self, function: Function) -> Result<FunctionExpr, ParseError> {
+        let start = self.pos;
+        self.expect('(')?;
+        self.skip_blank();
+        let mut arguments = Vec::new();
+        if !self.eat(')') {
+            loop {
+                arguments.push(self.function_argument()?);
+                self.skip_blank();
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(',')?;
+                self.skip_blank();
+            }
+        }
+        let parameters = function.parameters();
+        if parameters.len() != arguments.len() {
+            self.pos = start;
+            return self.error("wrong number of function arguments");
+        }
+        for (parameter, argument) in parameters.iter().zip(&arguments) {
+            if !is_well_typed(*parameter, argument) {
+                self.pos = start;
+                return self.error("function argument has the wrong type");
+            }
+        }
+        Ok(FunctionExpr {
+            function,
+            arguments,
+        })
+    }
+
+    fn function_argument(&mut self) -> Result<FunctionArgument, ParseError> {
+        let start = self.pos;
+        if !matches!(self.peek(), Some('!' | '(')) {
+            let primary = self.primary()?;
+            self.skip_blank();
+            if matches!(self.peek(), Some(',' | ')')) {
+                return Ok(match primary {
+                    Primary::Literal(value) => FunctionArgument::Literal(value),
+                    Primary::Query(query) => FunctionArgument::Query(query),
+                    Primary::Function(function) => FunctionArgument::Function(function),
+                });
+            }
+            self.pos = start;
+        }
+        Ok(FunctionArgument::Logical(self.logical_or()?))
+    }
+}
+
+/// Checks if `argument` can be passed as a parameter of type `parameter`.
+fn is_well_typed(parameter: FunctionType, argument: &FunctionArgument) -> bool {
+    match (parameter, argument) {
+        (FunctionType::Value, FunctionArgument::Literal(_))
+        | (FunctionType::Logical | FunctionType::Nodes, FunctionArgument::Query(_))
+        | (FunctionType::Logical, FunctionArgument::Logical(_)) => true,
+        (FunctionType::Value, FunctionArgument::Query(query)) => query.is_singular(),
+        (FunctionType::Value, FunctionArgument::Function(function)) => {
+            function.function.result() == FunctionType::Value
+        }
+        (FunctionType::Logical, FunctionArgument::Function(function)) => {
+            function.function.result() != FunctionType::Value
+        }
+        (FunctionType::Nodes, FunctionArgument::Function(function)) => {
+            function.function.result() == FunctionType::Nodes
+        }
+        _ => false,
+    }
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c >= '\u{80}'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_first(c) || c.is_ascii_digit()
+}
@@ -157,7 +157,6 @@ impl Sort for Map<String, Value> {
         *self = self.clone().sorted_keys_unstable_by::<F>(cmp);
     }
 
-    #[must_use]
     fn sorted_keys_by<F>(self, cmp: &mut F) -> Self
     where
         F: FnMut(&IndexPath, &Value, &IndexPath, &Value) -> Ordering,
@@ -170,7 +169,6 @@ impl Sort for Map<String, Value> {
         entries.into_iter().collect()
     }
 
-    #[must_use]
     fn sorted_keys_unstable_by<F>(self, cmp: &mut F) -> Self
     where
         F: FnMut(&IndexPath, &Value, &IndexPath, &Value) -> Ordering,
@@ -1,3 +1,8 @@
+#[cfg(any(feature = "patch", feature = "query"))]
+use serde_json::{Number, Value};
+#[cfg(any(feature = "patch", feature = "query"))]
+use std::cmp::Ordering;
+
 #[derive(Debug)]
 pub struct Split<'r, 't> {
     finder: fancy_regex::Matches<'r, 't>,
@@ -33,5 +38,43 @@ impl<'t> Iterator for Split<'_, 't> {
     }
 }
 
+/// Compares JSON values like `==`, except that numbers are compared by value,
+/// e.g. `1` and `1.0` are equal.
+#[cfg(any(feature = "patch", feature = "query"))]
+pub fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            compare_numbers(left, right) == Some(Ordering::Equal)
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| values_equal(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(key, left)| {
+                    right
+                        .get(key)
+                        .is_some_and(|right| values_equal(left, right))
+                })
+        }
+        (left, right) => left == right,
+    }
+}
+
+#[cfg(any(feature = "patch", feature = "query"))]
+pub fn compare_numbers(left: &Number, right: &Number) -> Option<Ordering> {
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        return Some(left.cmp(&right));
+    }
+    if let (Some(left), Some(right)) = (left.as_u64(), right.as_u64()) {
+        return Some(left.cmp(&right));
+    }
+    left.as_f64()?.partial_cmp(&right.as_f64()?)
+}
+
 #[cfg(test)]
 pub mod test {}